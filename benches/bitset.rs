@@ -0,0 +1,152 @@
+//! Compares the `HashSet`/nested-scan versions of days 3 and 6 against `BitSet`.
+//!
+//! Run with `cargo bench --bench bitset`.
+
+use advent_2022::bitset::{BitSet256, BitSet64};
+mod common;
+
+use common::bench;
+use std::collections::HashSet;
+
+const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// A tiny xorshift, so the inputs are the same on every run without pulling in `rand`.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn letter(&mut self) -> char {
+        ALPHABET[self.next() as usize % ALPHABET.len()] as char
+    }
+}
+
+fn priority(c: char) -> usize {
+    match c {
+        'a'..='z' => c as usize - 'a' as usize + 1,
+        _ => c as usize - 'A' as usize + 27,
+    }
+}
+
+fn rucksacks(rng: &mut Rng) -> Vec<String> {
+    (0..3000)
+        .map(|_| (0..48).map(|_| rng.letter()).collect())
+        .collect()
+}
+
+fn day_3_hash_set(lines: &[String]) -> usize {
+    let compartments: Vec<(HashSet<char>, HashSet<char>)> = lines
+        .iter()
+        .map(|line| line.split_at(line.len() / 2))
+        .map(|(left, right)| (left.chars().collect(), right.chars().collect()))
+        .collect();
+
+    let part_1: usize = compartments
+        .iter()
+        .flat_map(|(left, right)| left.intersection(right))
+        .map(|c| priority(*c))
+        .sum();
+
+    let rucksacks: Vec<HashSet<&char>> = compartments
+        .iter()
+        .map(|(left, right)| HashSet::from_iter(left.union(right)))
+        .collect();
+
+    let part_2: usize = rucksacks
+        .chunks(3)
+        .flat_map(|group| {
+            HashSet::from_iter(group[0].intersection(&group[1]).copied())
+                .intersection(&group[2])
+                .copied()
+                .collect::<Vec<&char>>()
+        })
+        .map(|c| priority(*c))
+        .sum();
+
+    part_1 + part_2
+}
+
+fn day_3_bit_set(lines: &[String]) -> usize {
+    let compartments: Vec<(BitSet64, BitSet64)> = lines
+        .iter()
+        .map(|line| line.split_at(line.len() / 2))
+        .map(|(left, right)| {
+            (
+                left.chars().map(priority).collect(),
+                right.chars().map(priority).collect(),
+            )
+        })
+        .collect();
+
+    let part_1: usize = compartments
+        .iter()
+        .flat_map(|(left, right)| *left & *right)
+        .sum();
+
+    let part_2: usize = compartments
+        .chunks(3)
+        .flat_map(|group| {
+            group
+                .iter()
+                .map(|(left, right)| *left | *right)
+                .reduce(|common, rucksack| common & rucksack)
+                .unwrap_or_default()
+        })
+        .sum();
+
+    part_1 + part_2
+}
+
+fn day_6_nested_scan<const N: usize>(signal: &[u8]) -> Option<usize> {
+    signal
+        .windows(N)
+        .position(|window| {
+            window
+                .iter()
+                .all(|val| window.iter().filter(|other| val == *other).take(2).count() == 1)
+        })
+        .map(|i| i + N)
+}
+
+fn day_6_bit_set<const N: usize>(signal: &[u8]) -> Option<usize> {
+    signal
+        .windows(N)
+        .position(|window| {
+            let mut seen = BitSet256::new();
+            window.iter().all(|val| seen.insert(*val as usize))
+        })
+        .map(|i| i + N)
+}
+
+fn main() {
+    let mut rng = Rng(0x2022_1203);
+
+    let lines = rucksacks(&mut rng);
+    assert_eq!(day_3_hash_set(&lines), day_3_bit_set(&lines));
+
+    bench("day 3: HashSet", 100, || day_3_hash_set(&lines));
+    bench("day 3: BitSet64", 100, || day_3_bit_set(&lines));
+
+    // fewer distinct letters than the window, so the marker is right at the end
+    let mut signal: Vec<u8> = (0..1_000_000)
+        .map(|_| b"abcdefghijklm"[rng.next() as usize % 13])
+        .collect();
+    signal.extend(b"nopqrstuvwxyz!");
+
+    assert_eq!(
+        day_6_nested_scan::<14>(&signal),
+        day_6_bit_set::<14>(&signal)
+    );
+
+    bench("day 6: nested scan (N = 14)", 10, || {
+        day_6_nested_scan::<14>(&signal)
+    });
+    bench("day 6: BitSet256 (N = 14)", 10, || {
+        day_6_bit_set::<14>(&signal)
+    });
+}
@@ -0,0 +1,14 @@
+//! Helpers shared by the benchmarks.
+
+use std::{hint::black_box, time::Instant};
+
+/// Runs `f` `iterations` times and prints how long each run took on average.
+pub fn bench<T>(name: &str, iterations: u32, mut f: impl FnMut() -> T) {
+    let start = Instant::now();
+
+    for _ in 0..iterations {
+        black_box(f());
+    }
+
+    println!("{name:<36} {:>10.2?}/iter", start.elapsed() / iterations);
+}
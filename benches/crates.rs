@@ -0,0 +1,144 @@
+//! Compares moving day 5's crates one at a time against moving them in bulk, on stacks that are
+//! much taller than the puzzle's and with many more moves.
+//!
+//! The draining versions are the moves day 5's cranes make, from [`advent_2022::stacks`].
+//!
+//! Run with `cargo bench --bench crates`.
+
+mod common;
+
+use advent_2022::{
+    rng::Rng,
+    stacks::{move_in_order, move_reversed},
+};
+use common::bench;
+
+type Stacks = Vec<Vec<String>>;
+
+/// `(count, from, to)`, with stacks counting from 0.
+type Move = (usize, usize, usize);
+
+/// Tall stacks, and moves that are always possible when made in order.
+fn generate(rng: &mut Rng, stacks: usize, height: usize, moves: usize) -> (Stacks, Vec<Move>) {
+    let labels: Vec<String> = ('A'..='Z').map(String::from).collect();
+
+    let stacks: Stacks = (0..stacks)
+        .map(|_| {
+            (0..height)
+                .map(|_| rng.choose(&labels).unwrap().clone())
+                .collect()
+        })
+        .collect();
+
+    let mut heights: Vec<usize> = stacks.iter().map(Vec::len).collect();
+
+    let moves = (0..moves)
+        .map(|_| {
+            let from = loop {
+                let from = rng.below(heights.len());
+
+                if heights[from] > 0 {
+                    break from;
+                }
+            };
+
+            let to = rng.below(heights.len());
+            let count = rng.below(heights[from].min(50)) + 1;
+
+            heights[from] -= count;
+            heights[to] += count;
+
+            (count, from, to)
+        })
+        .collect();
+
+    (stacks, moves)
+}
+
+fn tops(stacks: &Stacks) -> String {
+    stacks
+        .iter()
+        .filter_map(|stack| stack.last())
+        .cloned()
+        .collect()
+}
+
+fn one_at_a_time_popping(mut stacks: Stacks, moves: &[Move]) -> String {
+    for (count, from, to) in moves.iter().copied() {
+        for _ in 0..count {
+            let a_crate = stacks[from].pop().unwrap();
+            stacks[to].push(a_crate)
+        }
+    }
+
+    tops(&stacks)
+}
+
+fn one_at_a_time_draining(mut stacks: Stacks, moves: &[Move]) -> String {
+    for (count, from, to) in moves.iter().copied() {
+        if let Ok([from, to]) = stacks.get_disjoint_mut([from, to]) {
+            move_reversed(from, to, count);
+        }
+    }
+
+    tops(&stacks)
+}
+
+fn together_popping(mut stacks: Stacks, moves: &[Move]) -> String {
+    for (count, from, to) in moves.iter().copied() {
+        let mut crates = vec![];
+
+        for _ in 0..count {
+            let a_crate = stacks[from].pop().unwrap();
+            crates.push(a_crate);
+        }
+
+        crates
+            .into_iter()
+            .rev()
+            .for_each(|a_crate| stacks[to].push(a_crate));
+    }
+
+    tops(&stacks)
+}
+
+fn together_draining(mut stacks: Stacks, moves: &[Move]) -> String {
+    for (count, from, to) in moves.iter().copied() {
+        if let Ok([from, to]) = stacks.get_disjoint_mut([from, to]) {
+            move_in_order(from, to, count);
+        }
+    }
+
+    tops(&stacks)
+}
+
+fn main() {
+    let mut rng = Rng::new(0x2022_1205);
+
+    let (stacks, moves) = generate(&mut rng, 9, 100_000, 1_000_000);
+
+    assert_eq!(
+        one_at_a_time_popping(stacks.clone(), &moves),
+        one_at_a_time_draining(stacks.clone(), &moves)
+    );
+    assert_eq!(
+        together_popping(stacks.clone(), &moves),
+        together_draining(stacks.clone(), &moves)
+    );
+
+    // cloning the stacks is part of every iteration, so it's timed on its own too
+    bench("clone stacks", 5, || stacks.clone());
+
+    bench("9000: pop and push", 5, || {
+        one_at_a_time_popping(stacks.clone(), &moves)
+    });
+    bench("9000: drain and extend", 5, || {
+        one_at_a_time_draining(stacks.clone(), &moves)
+    });
+    bench("9001: pop, reverse and push", 5, || {
+        together_popping(stacks.clone(), &moves)
+    });
+    bench("9001: drain and extend", 5, || {
+        together_draining(stacks.clone(), &moves)
+    });
+}
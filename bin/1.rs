@@ -1,48 +1,221 @@
-use std::{collections::BinaryHeap, fs::read_to_string};
+use advent_2022::{
+    parse::{self, groups},
+    top_k::{top_k, top_k_indexed, TopK},
+};
+use std::{
+    fmt,
+    fs::read_to_string,
+    io::{self, BufRead},
+};
 
 const PROBLEM: u8 = 1;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // e.g. `generate-inventory | cargo run --bin 1 -- --stream`
+    if std::env::args().any(|arg| arg == "--stream") {
+        let top_three = stream_elves(io::stdin().lock(), 3)?;
+
+        println!("Problem {PROBLEM}");
+        println!("part-1: {}", top_three.first().copied().unwrap_or_default());
+        println!("part-2: {}", find_largest_three(&top_three)?);
+
+        return Ok(());
+    }
+
     let input = read_to_string(format!("inputs/{PROBLEM}.txt"))?;
 
-    let elves = parse_elves_input(&input);
+    let elves = parse_elves_input(&input)?;
+
+    let report_size = std::env::args()
+        .skip_while(|arg| arg != "--report")
+        .nth(1)
+        .map(|n| n.parse::<usize>())
+        .transpose()?;
 
     let largest = find_largest(&elves);
-    let largest_three = find_largest_three(&elves);
+    let largest_three = find_largest_three(&elves)?;
 
     println!("Problem {PROBLEM}");
     println!("part-1: {largest}");
     println!("part-2: {largest_three}");
 
+    if let Some(report) = report_size
+        .map(|n| report(&elves, n))
+        .transpose()?
+        .flatten()
+    {
+        print!("{report}");
+    }
+
     Ok(())
 }
 
-fn parse_elves_input(input: &str) -> Vec<usize> {
-    input.trim().lines().fold(vec![0], |mut elves, line| {
-        let calories: usize = line.trim().parse().unwrap_or_default();
+fn parse_elves_input(input: &str) -> Result<Vec<usize>, Error> {
+    groups(input)
+        .map(|elf| {
+            elf.numbered().try_fold(0usize, |total, (position, line)| {
+                let calories = line.parse::<usize>().map_err(|_| {
+                    Error::Calories(parse::Error {
+                        position,
+                        expected: String::from("a number of calories"),
+                    })
+                })?;
 
-        if calories > 0 {
-            // elves is never empty
-            *elves.last_mut().unwrap() += calories;
-        } else {
-            elves.push(0);
+                total.checked_add(calories).ok_or(Error::Overflow {
+                    line: position.line,
+                })
+            })
+        })
+        .collect()
+}
+
+fn find_largest(elves: &[usize]) -> usize {
+    elves.iter().max().copied().unwrap_or_default()
+}
+
+fn find_largest_three(elves: &[usize]) -> Result<usize, Error> {
+    checked_sum(top_k(elves.iter().copied(), 3))
+}
+
+fn checked_sum(calories: impl IntoIterator<Item = usize>) -> Result<usize, Error> {
+    calories
+        .into_iter()
+        .try_fold(0usize, |total, calories| total.checked_add(calories))
+        .ok_or(Error::TotalOverflow)
+}
+
+#[derive(Debug)]
+enum Error {
+    Io(io::Error),
+    Calories(parse::Error),
+    /// An elf's total no longer fits in a `usize` as of this line.
+    Overflow {
+        line: usize,
+    },
+    /// A total across several elves doesn't fit in a `usize`.
+    TotalOverflow,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{err}"),
+            Error::Calories(err) => write!(f, "{err}"),
+            Error::Overflow { line } => {
+                write!(f, "line {line}: total calories overflowed a usize")
+            }
+            Error::TotalOverflow => write!(f, "total calories across elves overflowed a usize"),
         }
+    }
+}
+
+impl std::error::Error for Error {}
 
-        elves
-    })
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
 }
 
-fn find_largest(elves: &[usize]) -> usize {
-    *elves.iter().max().unwrap()
+/// The `k` largest elf totals, largest first, read a line at a time.
+///
+/// Only the elf currently being read and the top `k` are kept in memory, so the input can be
+/// arbitrarily large.
+fn stream_elves(mut reader: impl BufRead, k: usize) -> Result<Vec<usize>, Error> {
+    let mut top = TopK::new(k);
+    let mut elf: Option<usize> = None;
+    let mut line = String::new();
+    let mut line_number = 0;
+
+    loop {
+        line.clear();
+        let is_done = reader.read_line(&mut line)? == 0;
+        line_number += 1;
+
+        let calories = line.trim();
+
+        if calories.is_empty() {
+            top.extend(elf.take());
+
+            if is_done {
+                return Ok(top.into_sorted_vec());
+            }
+
+            continue;
+        }
+
+        let calories: usize = calories.parse().map_err(|_| {
+            Error::Calories(parse::Error {
+                position: parse::Position {
+                    line: line_number,
+                    column: line.chars().take_while(|c| c.is_whitespace()).count() + 1,
+                },
+                expected: String::from("a number of calories"),
+            })
+        })?;
+
+        let total = elf
+            .unwrap_or_default()
+            .checked_add(calories)
+            .ok_or(Error::Overflow { line: line_number })?;
+
+        elf = Some(total);
+    }
 }
 
-fn find_largest_three(elves: &[usize]) -> usize {
-    elves
-        .iter()
-        .collect::<BinaryHeap<_>>()
+#[derive(Debug, PartialEq)]
+struct Report {
+    /// The elves carrying the most calories, as `(elf number, calories)`, starting at elf 1.
+    top: Vec<(usize, usize)>,
+    total: usize,
+    mean: f64,
+    median: f64,
+}
+
+/// Statistics across all the elves, along with the top `n`. `None` if there are no elves.
+fn report(elves: &[usize], n: usize) -> Result<Option<Report>, Error> {
+    if elves.is_empty() {
+        return Ok(None);
+    }
+
+    let top = top_k_indexed(elves.iter().copied(), n)
         .into_iter()
-        .take(3)
-        .sum()
+        .map(|(i, calories)| (i + 1, calories))
+        .collect();
+
+    let total = checked_sum(elves.iter().copied())?;
+    let mean = total as f64 / elves.len() as f64;
+
+    let mut sorted = elves.to_vec();
+    let mid = sorted.len() / 2;
+    let (lower, upper, _) = sorted.select_nth_unstable(mid);
+
+    let median = if elves.len().is_multiple_of(2) {
+        // the other middle value is the largest of the lower half
+        let other = lower.iter().max().unwrap();
+        (*upper as f64 + *other as f64) / 2.0
+    } else {
+        *upper as f64
+    };
+
+    Ok(Some(Report {
+        top,
+        total,
+        mean,
+        median,
+    }))
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (rank, (elf, calories)) in self.top.iter().enumerate() {
+            writeln!(f, "#{}: elf {elf} with {calories}", rank + 1)?;
+        }
+
+        writeln!(f, "total: {}", self.total)?;
+        writeln!(f, "mean: {:.1}", self.mean)?;
+        writeln!(f, "median: {:.1}", self.median)
+    }
 }
 
 #[cfg(test)]
@@ -68,7 +241,7 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let elves = parse_elves_input(INPUT);
+        let elves = parse_elves_input(INPUT).unwrap();
 
         let largest = find_largest(&elves);
 
@@ -77,10 +250,124 @@ mod tests {
 
     #[test]
     fn part_2() {
-        let elves = parse_elves_input(INPUT);
+        let elves = parse_elves_input(INPUT).unwrap();
 
-        let largest = find_largest_three(&elves);
+        let largest = find_largest_three(&elves).unwrap();
 
         assert_eq!(largest, 45000);
     }
+
+    #[test]
+    fn zero_calories_do_not_start_a_new_elf() {
+        let elves = parse_elves_input("1000\n0\n2000\n\n0\n\n3000").unwrap();
+
+        assert_eq!(elves, [3000, 0, 3000]);
+    }
+
+    #[test]
+    fn no_elves() {
+        let elves = parse_elves_input("\n  \n").unwrap();
+
+        assert!(elves.is_empty());
+        assert_eq!(find_largest(&elves), 0);
+        assert_eq!(find_largest_three(&elves).unwrap(), 0);
+    }
+
+    #[test]
+    fn crlf_and_indentation() {
+        let elves = parse_elves_input("  1000\r\n  2000\r\n  \r\n  3000\r\n").unwrap();
+
+        assert_eq!(elves, [3000, 3000]);
+    }
+
+    #[test]
+    fn malformed_lines_are_errors() {
+        let err = parse_elves_input(INPUT.replace("5000", "five").as_str()).unwrap_err();
+
+        let Error::Calories(err) = err else {
+            panic!("expected a calories error, got {err:?}");
+        };
+
+        assert_eq!(err.position, parse::Position { line: 8, column: 9 });
+        assert_eq!(err.expected, "a number of calories");
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        let input = format!("1\n\n{}\n1\n", usize::MAX);
+        let err = parse_elves_input(&input).unwrap_err();
+
+        assert!(matches!(err, Error::Overflow { line: 4 }));
+
+        let elves = parse_elves_input(&format!("1\n\n{}\n", usize::MAX)).unwrap();
+
+        assert!(matches!(
+            find_largest_three(&elves),
+            Err(Error::TotalOverflow)
+        ));
+        assert!(matches!(
+            super::report(&elves, 1),
+            Err(Error::TotalOverflow)
+        ));
+    }
+
+    #[test]
+    fn report() {
+        let elves = parse_elves_input(INPUT).unwrap();
+
+        let report = super::report(&elves, 3).unwrap().unwrap();
+
+        assert_eq!(
+            report,
+            Report {
+                top: vec![(4, 24000), (3, 11000), (5, 10000)],
+                total: 55000,
+                mean: 11000.0,
+                median: 10000.0,
+            }
+        );
+    }
+
+    #[test]
+    fn report_median_of_even_count() {
+        let report = super::report(&[4, 1, 3, 2], 1).unwrap().unwrap();
+
+        assert_eq!(report.top, [(1, 4)]);
+        assert_eq!(report.median, 2.5);
+        assert_eq!(super::report(&[], 1).unwrap(), None);
+    }
+
+    #[test]
+    fn stream() {
+        let top = stream_elves(INPUT.as_bytes(), 3).unwrap();
+
+        assert_eq!(top, [24000, 11000, 10000]);
+    }
+
+    #[test]
+    fn stream_without_trailing_newline() {
+        let top = stream_elves("1\r\n0\r\n\r\n2\r\n3".as_bytes(), 3).unwrap();
+
+        assert_eq!(top, [5, 1]);
+    }
+
+    #[test]
+    fn stream_detects_overflow() {
+        let input = format!("1\n\n{}\n1\n", usize::MAX);
+
+        let err = stream_elves(input.as_bytes(), 3).unwrap_err();
+
+        assert!(matches!(err, Error::Overflow { line: 4 }));
+    }
+
+    #[test]
+    fn stream_reports_malformed_lines() {
+        let err = stream_elves(INPUT.replace("5000", "five").as_bytes(), 3).unwrap_err();
+
+        let Error::Calories(err) = err else {
+            panic!("expected a calories error, got {err:?}");
+        };
+
+        assert_eq!(err.position, parse::Position { line: 8, column: 9 });
+    }
 }
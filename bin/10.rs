@@ -52,7 +52,7 @@ impl CycleCount {
     }
 
     fn decr(&mut self) {
-        self.0 = self.0.checked_sub(1).unwrap_or_default();
+        self.0 = self.0.saturating_sub(1);
     }
 }
 
@@ -357,6 +357,7 @@ noop
     }
 
     #[test]
+    #[allow(clippy::let_unit_value, clippy::unit_cmp)]
     fn part_2() {
         let parsed = super::parse_input(INPUT);
         let part_2_ans = super::part_2(&parsed);
@@ -1,22 +1,34 @@
-use std::{
-    cell::RefCell,
-    cmp::Ordering,
-    fs::read_to_string,
-    num::ParseIntError,
-    str::{FromStr, Lines},
+use advent_2022::parse::{
+    self, alt, blocks, field, line, map, opt, pair, preceded, separated, tag, terminated, unsigned,
+    Input, PResult, Parser,
 };
+use advent_2022::{
+    cycle::{self, Accumulator, Overflow},
+    top_k::top,
+};
+use std::{cell::RefCell, fs::read_to_string};
 
 const PROBLEM: u8 = 11;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input = read_to_string(format!("inputs/{PROBLEM}.txt"))?;
 
-    let parsed = parse_input(&input);
+    let parsed = parse_input(&input)?;
+
+    let rounds = std::env::args()
+        .skip_while(|arg| arg != "--rounds")
+        .nth(1)
+        .map(|rounds| rounds.parse::<usize>())
+        .transpose()?;
 
     println!("Problem {PROBLEM}");
     println!("part-1: {:?}", part_1(&parsed));
     println!("part-2: {:?}", part_2(&parsed));
 
+    if let Some(rounds) = rounds {
+        println!("rounds-{rounds}: {:?}", monkey_business(&parsed, rounds)?);
+    }
+
     Ok(())
 }
 
@@ -45,6 +57,29 @@ impl Monkey {
         }
     }
 
+    fn parse(input: Input<'_>) -> PResult<'_, Self> {
+        let header = line(terminated(
+            preceded(tag("Monkey "), unsigned::<usize>()),
+            tag(":"),
+        ));
+
+        let items = field(
+            "Starting items:",
+            map(
+                opt(separated(map(unsigned(), Item), tag(", "))),
+                Option::unwrap_or_default,
+            ),
+        );
+
+        let operation = field("Operation:", Operation::parse);
+
+        map(
+            preceded(header, pair(items, pair(operation, Test::parse))),
+            |(items, (operation, test))| Self::new(items, operation, test),
+        )
+        .parse(input)
+    }
+
     fn inspect_and_throw_items<const N: usize>(&mut self, monkeys: &Monkeys, magic_number: usize) {
         let items = std::mem::take(&mut self.items).into_iter();
 
@@ -79,6 +114,21 @@ enum Operation {
 }
 
 impl Operation {
+    fn parse(input: Input<'_>) -> PResult<'_, Self> {
+        let operand = alt(map(tag("old"), |_| None), map(unsigned(), Some));
+
+        map(
+            preceded(tag("new = old "), pair(alt(tag("* "), tag("+ ")), operand)),
+            |operation| match operation {
+                ("+ ", Some(other)) => Self::Add(other),
+                ("+ ", None) => Self::SelfAdd,
+                (_, Some(other)) => Self::Mul(other),
+                (_, None) => Self::SelfMul,
+            },
+        )
+        .parse(input)
+    }
+
     fn operate(&self, item: Item) -> Item {
         match self {
             Operation::SelfMul => Item(item.0 * item.0),
@@ -89,27 +139,6 @@ impl Operation {
     }
 }
 
-impl FromStr for Operation {
-    type Err = ParseIntError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut chars = s.split(' ');
-
-        let result = match chars.next() {
-            Some("+") => match chars.next().unwrap().parse() {
-                Ok(other) => Self::Add(other),
-                _ => Self::SelfAdd,
-            },
-            _ => match chars.next().unwrap().parse() {
-                Ok(other) => Self::Mul(other),
-                _ => Self::SelfMul,
-            },
-        };
-
-        Ok(result)
-    }
-}
-
 #[derive(Clone, Copy, Debug)]
 struct Test {
     divisor: usize,
@@ -118,8 +147,21 @@ struct Test {
 }
 
 impl Test {
+    fn parse(input: Input<'_>) -> PResult<'_, Self> {
+        let divisor = field("Test:", preceded(tag("divisible by "), unsigned()));
+        let t = field("If true:", preceded(tag("throw to monkey "), unsigned()));
+        let f = field("If false:", preceded(tag("throw to monkey "), unsigned()));
+
+        map(pair(divisor, pair(t, f)), |(divisor, (t, f))| Self {
+            divisor,
+            t,
+            f,
+        })
+        .parse(input)
+    }
+
     fn test(&self, item: Item) -> usize {
-        if item.0 % self.divisor == 0 {
+        if item.0.is_multiple_of(self.divisor) {
             self.t
         } else {
             self.f
@@ -127,97 +169,50 @@ impl Test {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 struct Item(usize);
 
-impl FromStr for Item {
-    type Err = ParseIntError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(s.parse()?))
-    }
+/// Which monkey is holding an item at the start of a round.
+///
+/// Items never affect each other, so each one can be followed through the rounds on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Holding {
+    monkey: usize,
+    item: Item,
 }
 
-#[derive(Debug)]
-struct TwoLargest([usize; 2]);
+impl Holding {
+    fn play_round(&self, monkeys: &[Monkey], magic_number: usize) -> (Self, Vec<u128>) {
+        let mut counts = vec![0; monkeys.len()];
+        let Self {
+            mut monkey,
+            mut item,
+        } = *self;
 
-impl TwoLargest {
-    fn new() -> Self {
-        Self([0; 2])
-    }
+        loop {
+            counts[monkey] += 1;
 
-    fn insert(&mut self, val: usize) -> bool {
-        match (self.0[0].cmp(&val), self.0[1].cmp(&val)) {
-            (Ordering::Less, Ordering::Less) => {
-                self.0[0] = self.0[1];
-                self.0[1] = val;
-                true
-            }
-            (Ordering::Less, _) => {
-                self.0[0] = val;
-                true
-            }
-            _ => false,
-        }
-    }
-}
+            let current = &monkeys[monkey];
+            item = Item(current.operation.operate(item).0 % magic_number);
 
-impl From<TwoLargest> for usize {
-    fn from(two: TwoLargest) -> Self {
-        two.0.into_iter().product()
-    }
-}
+            let recipient = current.test(item);
+            // monkeys take turns in order, so an item thrown forward gets inspected again this round
+            let is_done = recipient <= monkey;
+            monkey = recipient;
 
-fn parse_monkey(mut monkeys: Monkeys, mut lines: Lines) -> Monkeys {
-    match lines.next() {
-        Some(line) if line.starts_with("Monkey ") => {
-            let items = parse_items(lines.next().unwrap());
-            let operation = parse_operation(lines.next().unwrap());
-            let (lines, test) = parse_test(lines);
-
-            monkeys.push(RefCell::new(Monkey::new(items, operation, test)));
-
-            parse_monkey(monkeys, lines)
+            if is_done {
+                break;
+            }
         }
-        Some(_) => parse_monkey(monkeys, lines),
-        None => monkeys,
-    }
-}
-
-fn parse_items(s: &str) -> Items {
-    s.trim()
-        .strip_prefix("Starting items: ")
-        .and_then(|s| {
-            s.split(", ")
-                .map(FromStr::from_str)
-                .collect::<Result<Items, _>>()
-                .ok()
-        })
-        .unwrap_or_default()
-}
 
-fn parse_operation(s: &str) -> Operation {
-    s.trim()
-        .strip_prefix("Operation: new = old ")
-        .and_then(|s| s.parse().ok())
-        .unwrap()
+        (Self { monkey, item }, counts)
+    }
 }
 
-fn parse_test(mut lines: Lines) -> (Lines, Test) {
-    let mut values = [lines.next(), lines.next(), lines.next()]
-        .into_iter()
-        .flatten()
-        .flat_map(|line| line.split(' ').filter_map(|val| usize::from_str(val).ok()));
-
-    let divisor = values.next().unwrap();
-    let t = values.next().unwrap();
-    let f = values.next().unwrap();
+fn parse_input(input: &str) -> Result<Parsed, parse::Error> {
+    let monkeys = parse::parse(input, blocks(Monkey::parse))?;
 
-    (lines, Test { divisor, t, f })
-}
-
-fn parse_input(input: &str) -> Parsed {
-    parse_monkey(vec![], input.trim().lines())
+    Ok(monkeys.into_iter().map(RefCell::new).collect())
 }
 
 fn part_1(parsed: &Parsed) -> Part1 {
@@ -230,20 +225,20 @@ fn part_1(parsed: &Parsed) -> Part1 {
         }
     }
 
-    monkeys
+    let counts = monkeys
         .into_iter()
-        .map(|monkey| monkey.borrow().inspection_count)
-        .fold(TwoLargest::new(), |mut largest, count| {
-            largest.insert(count);
-            largest
-        })
-        .into()
+        .map(|monkey| monkey.borrow().inspection_count);
+
+    top::<2, _>(counts).map_or(0, |[a, b]| a * b)
 }
 
 fn part_2(parsed: &Parsed) -> Part2 {
     let monkeys = parsed.clone();
 
-    let magic_number = monkeys.iter().map(|monkey| monkey.borrow().test.divisor).product();
+    let magic_number = monkeys
+        .iter()
+        .map(|monkey| monkey.borrow().test.divisor)
+        .product();
 
     for _round in 0..10_000 {
         for i in 0..monkeys.len() {
@@ -252,14 +247,45 @@ fn part_2(parsed: &Parsed) -> Part2 {
         }
     }
 
-    monkeys
+    let counts = monkeys
         .into_iter()
-        .map(|monkey| monkey.borrow().inspection_count)
-        .fold(TwoLargest::new(), |mut largest, count| {
-            largest.insert(count);
-            largest
+        .map(|monkey| monkey.borrow().inspection_count);
+
+    top::<2, _>(counts).map_or(0, |[a, b]| a * b)
+}
+
+/// Same rules as `part_2`, but only simulates each item until it starts repeating itself.
+///
+/// Enough rounds can make even a `u128` overflow, which is an error rather than a wrong answer.
+fn monkey_business(parsed: &Parsed, rounds: usize) -> Result<u128, Overflow> {
+    let monkeys: Vec<Monkey> = parsed
+        .iter()
+        .map(|monkey| monkey.borrow().clone())
+        .collect();
+
+    let magic_number = monkeys.iter().map(|monkey| monkey.test.divisor).product();
+
+    let step = |holding: &Holding| holding.play_round(&monkeys, magic_number);
+
+    let counts = monkeys
+        .iter()
+        .enumerate()
+        .flat_map(|(i, monkey)| {
+            monkey.items.iter().map(move |item| Holding {
+                monkey: i,
+                item: Item(item.0 % magic_number),
+            })
+        })
+        .map(|holding| {
+            let cycle = cycle::brent(&holding, |holding| step(holding).0);
+            cycle.extrapolate(holding, rounds, step)
         })
-        .into()
+        .try_fold(Vec::new(), |mut total, counts| {
+            total.add(&counts?)?;
+            Ok(total)
+        })?;
+
+    top::<2, _>(counts).map_or(Ok(0), |[a, b]| a.checked_mul(b).ok_or(Overflow))
 }
 
 #[cfg(test)]
@@ -301,7 +327,7 @@ Monkey 3:
 
     #[test]
     fn part_1() {
-        let parsed = super::parse_input(INPUT);
+        let parsed = super::parse_input(INPUT).unwrap();
         let part_1_ans = super::part_1(&parsed);
 
         assert_eq!(part_1_ans, PART_1_TEST_ANS);
@@ -309,9 +335,42 @@ Monkey 3:
 
     #[test]
     fn part_2() {
-        let parsed = super::parse_input(INPUT);
+        let parsed = super::parse_input(INPUT).unwrap();
         let part_2_ans = super::part_2(&parsed);
 
         assert_eq!(part_2_ans, PART_2_TEST_ANS);
     }
+
+    #[test]
+    fn parse_error() {
+        let input = INPUT.replace("new = old + 6", "new = old - 6");
+        let err = super::parse_input(&input).unwrap_err();
+
+        assert_eq!(err.position.line, 11);
+        assert_eq!(err.position.column, 24);
+        assert_eq!(err.expected, r#""* " or "+ ""#);
+    }
+
+    #[test]
+    fn monkey_business() {
+        let parsed = super::parse_input(INPUT).unwrap();
+
+        assert_eq!(super::monkey_business(&parsed, 1_000), Ok(5204 * 5192));
+        assert_eq!(
+            super::monkey_business(&parsed, 10_000),
+            Ok(PART_2_TEST_ANS as u128)
+        );
+    }
+
+    #[test]
+    fn monkey_business_overflow() {
+        let parsed = super::parse_input(INPUT).unwrap();
+
+        // the answer is far too large for a `u64`
+        assert!(super::monkey_business(&parsed, 1_000_000_000_000_000_000).is_ok());
+        assert_eq!(
+            super::monkey_business(&parsed, usize::MAX),
+            Err(super::Overflow)
+        );
+    }
 }
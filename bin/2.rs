@@ -1,84 +1,296 @@
-use std::fs::read_to_string;
+use advent_2022::{
+    bitset::BitSet64,
+    parse::{self, char_if, lines, map, pair, preceded, spaces},
+    rng::Rng,
+};
+use std::{cmp::Reverse, fs::read_to_string};
 
 const PROBLEM: u8 = 2;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input = read_to_string(format!("inputs/{PROBLEM}.txt"))?;
 
-    let parsed = parse_input(&input);
+    let game = game_from_args()?;
+    let parsed = parse_input(&input, &game)?;
 
-    let part_1 = part_1(&parsed);
-    let part_2 = part_2(&parsed);
+    let part_1 = part_1(&game, &parsed);
+    let part_2 = part_2(&game, &parsed);
 
     println!("Problem {PROBLEM}");
     println!("part-1: {:?}", part_1);
     println!("part-2: {:?}", part_2);
 
+    let arg = |name: &str| std::env::args().skip_while(|arg| arg != name).nth(1);
+
+    if std::env::args().any(|arg| arg == "--analyze") {
+        print_analysis(&game, &analyze(&game, &parsed));
+    }
+
+    // e.g. `--tournament 100 --rounds 1000 --seed 42`
+    if let Some(tournaments) = arg("--tournament") {
+        let tournaments = tournaments.parse()?;
+        let rounds = arg("--rounds").map_or(Ok(parsed.len()), |rounds| rounds.parse())?;
+        let seed = arg("--seed").map_or(Ok(0), |seed| seed.parse())?;
+
+        let mut strategies = default_strategies(&game);
+        strategies.push(Box::new(Guide(
+            "guide (my column)",
+            parsed.iter().map(|(_, me)| *me).collect(),
+        )));
+        strategies.push(Box::new(Guide(
+            "guide (opponent column)",
+            parsed.iter().map(|(opponent, _)| *opponent).collect(),
+        )));
+
+        let standings = tournament(&game, &strategies, rounds, tournaments, seed);
+
+        for standing in standings {
+            let Tally {
+                wins,
+                draws,
+                losses,
+                score,
+            } = standing.tally;
+
+            println!(
+                "{:<24} score {score:>10}  W/D/L {wins}/{draws}/{losses}",
+                standing.name
+            );
+        }
+    }
+
     Ok(())
 }
 
-type Parsed = Vec<(Move, Move)>;
+/// `--game classic|rpsls|<number of moves>`, with optional `--move-scores 1,2,3` and
+/// `--outcome-scores 0,3,6` (loss, draw, win).
+fn game_from_args() -> Result<Game, Box<dyn std::error::Error>> {
+    let arg = |name: &str| std::env::args().skip_while(|arg| arg != name).nth(1);
 
-#[derive(Clone, Copy, Debug)]
-enum Move {
-    Rock,
-    Paper,
-    Scissors,
-}
+    let scores = |list: String| {
+        list.split(',')
+            .map(|score| score.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    let mut game = match arg("--game").as_deref() {
+        None | Some("classic") => Game::classic(),
+        Some("rpsls") => Game::rock_paper_scissors_lizard_spock(),
+        Some(size) => {
+            let size: usize = size.parse()?;
+
+            if size.is_multiple_of(2) || size > 26 {
+                return Err("a cyclic game needs an odd number of moves, up to 26".into());
+            }
+
+            // A, B, C, ... for the opponent and ..., X, Y, Z for me, like the puzzle
+            let opponent: String = ('A'..='Z').take(size).collect();
+            let me: String = ('A'..='Z').skip(26 - size).collect();
 
-impl From<&Move> for usize {
-    fn from(a_move: &Move) -> Self {
-        match a_move {
-            Move::Rock => 1,
-            Move::Paper => 2,
-            Move::Scissors => 3,
+            Game::cyclic(size, &opponent, &me)
         }
+    };
+
+    if let Some(move_scores) = arg("--move-scores") {
+        let move_scores = scores(move_scores)?;
+
+        if move_scores.len() != game.size() {
+            return Err(format!("need {} move scores", game.size()).into());
+        }
+
+        game = game.with_move_scores(move_scores);
     }
-}
 
-impl Move {
-    fn play(&self, other: &Self) -> usize {
-        let score = match (self, other) {
-            // tie
-            (Move::Rock, Move::Rock)
-            | (Move::Paper, Move::Paper)
-            | (Move::Scissors, Move::Scissors) => 3,
-            // win
-            (Move::Rock, Move::Scissors)
-            | (Move::Paper, Move::Rock)
-            | (Move::Scissors, Move::Paper) => 6,
-            // loss
-            _ => 0,
-        };
+    if let Some(outcome_scores) = arg("--outcome-scores") {
+        let outcome_scores = scores(outcome_scores)?
+            .try_into()
+            .map_err(|_| "need 3 outcome scores: loss, draw and win")?;
 
-        score + usize::from(self)
+        game = game.with_outcome_scores(outcome_scores);
     }
+
+    Ok(game)
 }
 
-#[derive(Debug)]
+type Parsed = Vec<(Move, Move)>;
+
+/// A move in a [`Game`], numbered from 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Move(usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Outcome {
-    Win,
     Loss,
     Draw,
+    Win,
+}
+
+/// A hand game like rock-paper-scissors, where some moves beat others.
+#[derive(Clone, Debug)]
+struct Game {
+    /// `beats[a]` holds every move that `a` beats.
+    beats: Vec<BitSet64>,
+    move_scores: Vec<usize>,
+    /// What a loss, a draw and a win are worth, in that order.
+    outcome_scores: [usize; 3],
+    opponent_symbols: Vec<char>,
+    my_symbols: Vec<char>,
 }
 
-impl Outcome {
-    fn get_my_move(&self, oponent: &Move) -> Move {
-        match (oponent, self) {
-            (Move::Rock, Outcome::Win) | (Move::Scissors, Outcome::Loss) => Move::Paper,
-            (Move::Rock, Outcome::Loss) | (Move::Paper, Outcome::Win) => Move::Scissors,
-            (Move::Paper, Outcome::Loss) | (Move::Scissors, Outcome::Win) => Move::Rock,
-            (oponent, Outcome::Draw) => *oponent,
+impl Game {
+    /// Moves are scored `1..=beats.len()` and outcomes score 0, 3 and 6, like the puzzle.
+    ///
+    /// Each symbol list names every move, in order. Two moves where neither beats the other are
+    /// a draw.
+    fn new(beats: Vec<BitSet64>, opponent_symbols: &str, my_symbols: &str) -> Self {
+        let size = beats.len();
+
+        assert!(size <= BitSet64::CAPACITY, "too many moves");
+
+        for (a, beaten) in beats.iter().enumerate() {
+            assert!(!beaten.contains(a), "move {a} can't beat itself");
+
+            for b in beaten {
+                assert!(b < size, "move {a} beats move {b}, which doesn't exist");
+                assert!(
+                    !beats[b].contains(a),
+                    "moves {a} and {b} can't beat each other"
+                );
+            }
+        }
+
+        let opponent_symbols: Vec<char> = opponent_symbols.chars().collect();
+        let my_symbols: Vec<char> = my_symbols.chars().collect();
+
+        assert_eq!(opponent_symbols.len(), size, "need a symbol for every move");
+        assert_eq!(my_symbols.len(), size, "need a symbol for every move");
+
+        Self {
+            beats,
+            move_scores: (1..=size).collect(),
+            outcome_scores: [0, 3, 6],
+            opponent_symbols,
+            my_symbols,
         }
     }
-}
 
-impl From<&Move> for Outcome {
-    fn from(a_move: &Move) -> Self {
-        match a_move {
-            Move::Rock => Outcome::Loss,
-            Move::Paper => Outcome::Draw,
-            Move::Scissors => Outcome::Win,
+    fn with_move_scores(self, move_scores: Vec<usize>) -> Self {
+        assert_eq!(
+            move_scores.len(),
+            self.size(),
+            "need a score for every move"
+        );
+
+        Self {
+            move_scores,
+            ..self
+        }
+    }
+
+    fn with_outcome_scores(self, outcome_scores: [usize; 3]) -> Self {
+        Self {
+            outcome_scores,
+            ..self
+        }
+    }
+
+    /// Rock, paper, scissors.
+    fn classic() -> Self {
+        Self::cyclic(3, "ABC", "XYZ")
+    }
+
+    /// Each move beats the moves an odd number of steps behind it around the cycle.
+    ///
+    /// With an odd number of moves, that means every move beats exactly half of the others.
+    fn cyclic(size: usize, opponent_symbols: &str, my_symbols: &str) -> Self {
+        assert!(size % 2 == 1, "a cyclic game needs an odd number of moves");
+
+        let beats = (0..size)
+            .map(|a| {
+                (0..size)
+                    .filter(|b| (a + size - b) % size % 2 == 1)
+                    .collect()
+            })
+            .collect();
+
+        Self::new(beats, opponent_symbols, my_symbols)
+    }
+
+    /// Rock, paper, scissors, lizard, Spock, in that order.
+    fn rock_paper_scissors_lizard_spock() -> Self {
+        const ROCK: usize = 0;
+        const PAPER: usize = 1;
+        const SCISSORS: usize = 2;
+        const LIZARD: usize = 3;
+        const SPOCK: usize = 4;
+
+        let beats = [
+            [SCISSORS, LIZARD],
+            [ROCK, SPOCK],
+            [PAPER, LIZARD],
+            [PAPER, SPOCK],
+            [ROCK, SCISSORS],
+        ];
+
+        let beats = beats
+            .into_iter()
+            .map(|beaten| beaten.into_iter().collect())
+            .collect();
+
+        Self::new(beats, "ABCDE", "VWXYZ")
+    }
+
+    fn size(&self) -> usize {
+        self.beats.len()
+    }
+
+    fn moves(&self) -> impl Iterator<Item = Move> {
+        (0..self.size()).map(Move)
+    }
+
+    fn outcome(&self, me: Move, opponent: Move) -> Outcome {
+        if self.beats[me.0].contains(opponent.0) {
+            Outcome::Win
+        } else if self.beats[opponent.0].contains(me.0) {
+            Outcome::Loss
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    fn play(&self, me: Move, opponent: Move) -> usize {
+        self.move_scores[me.0] + self.outcome_scores[self.outcome(me, opponent) as usize]
+    }
+
+    /// The best scoring move that gets `outcome` against `opponent`, if there is one.
+    fn get_my_move(&self, opponent: Move, outcome: Outcome) -> Option<Move> {
+        self.moves()
+            .filter(|me| self.outcome(*me, opponent) == outcome)
+            .max_by_key(|me| self.move_scores[me.0])
+    }
+
+    /// The highest scoring move against `opponent`.
+    fn best_response(&self, opponent: Move) -> Move {
+        self.moves()
+            .max_by_key(|me| self.play(*me, opponent))
+            .expect("a game has at least one move")
+    }
+
+    fn my_symbol(&self, a_move: Move) -> char {
+        self.my_symbols[a_move.0]
+    }
+
+    fn opponent_symbol(&self, a_move: Move) -> char {
+        self.opponent_symbols[a_move.0]
+    }
+
+    /// How part 2 reads the second column: as a dial from losing, through a draw in the middle,
+    /// to winning. With three moves that's just X, Y and Z.
+    fn outcome_for(&self, column: Move) -> Outcome {
+        match column.0.cmp(&(self.size() / 2)) {
+            std::cmp::Ordering::Less => Outcome::Loss,
+            std::cmp::Ordering::Equal => Outcome::Draw,
+            std::cmp::Ordering::Greater => Outcome::Win,
         }
     }
 }
@@ -87,39 +299,364 @@ type Part1 = usize;
 
 type Part2 = usize;
 
-fn parse_input(input: &str) -> Parsed {
-    input
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .map(|line| {
-            let mut moves = line.chars().filter_map(|c| match c {
-                'A' | 'X' => Some(Move::Rock),
-                'B' | 'Y' => Some(Move::Paper),
-                'C' | 'Z' => Some(Move::Scissors),
-                _ => None,
-            });
-
-            (moves.next().unwrap(), moves.next().unwrap())
-        })
-        .collect()
+fn parse_input(input: &str, game: &Game) -> Result<Parsed, parse::Error> {
+    let symbol = |symbols: Vec<char>| {
+        let is_symbol = symbols.clone();
+
+        map(
+            char_if("a move symbol", move |c| is_symbol.contains(&c)),
+            move |c| Move(symbols.iter().position(|symbol| *symbol == c).unwrap()),
+        )
+    };
+
+    let opponent = preceded(spaces, symbol(game.opponent_symbols.clone()));
+    let me = preceded(spaces, symbol(game.my_symbols.clone()));
+
+    parse::parse(input, lines(pair(opponent, me)))
 }
 
-fn part_1(parsed: &Parsed) -> Part1 {
-    parsed.iter().map(|(oponent, me)| me.play(oponent)).sum()
+fn part_1(game: &Game, parsed: &Parsed) -> Part1 {
+    parsed
+        .iter()
+        .map(|(oponent, me)| game.play(*me, *oponent))
+        .sum()
 }
 
-fn part_2(parsed: &Parsed) -> Part2 {
+fn part_2(game: &Game, parsed: &Parsed) -> Part2 {
     parsed
         .iter()
-        .map(|(oponent, me)| (oponent, Outcome::from(me)))
-        .map(|(oponent, outcome)| outcome.get_my_move(oponent).play(oponent))
+        .map(|(oponent, column)| (oponent, game.outcome_for(*column)))
+        .map(|(oponent, outcome)| {
+            let me = game
+                .get_my_move(*oponent, outcome)
+                .expect("every outcome should be possible against every move");
+
+            game.play(me, *oponent)
+        })
         .sum()
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Tally {
+    wins: usize,
+    draws: usize,
+    losses: usize,
+    score: usize,
+}
+
+impl Tally {
+    fn record(&mut self, outcome: Outcome, score: usize) {
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Loss => self.losses += 1,
+        }
+
+        self.score += score;
+    }
+}
+
+/// A single round of the guide, read both ways.
+#[derive(Debug, PartialEq)]
+struct Round {
+    opponent: Move,
+    column: Move,
+    /// Reading the column as my move, like `part_1`.
+    as_move: (Outcome, usize),
+    /// Reading the column as the outcome, like `part_2`, which decides my move.
+    as_outcome: (Move, Outcome, usize),
+    /// The best I could have done.
+    best: (Move, Outcome, usize),
+}
+
+#[derive(Debug, PartialEq)]
+struct Analysis {
+    rounds: Vec<Round>,
+    as_moves: Tally,
+    as_outcomes: Tally,
+    best: Tally,
+}
+
+fn analyze(game: &Game, parsed: &Parsed) -> Analysis {
+    let mut analysis = Analysis {
+        rounds: vec![],
+        as_moves: Tally::default(),
+        as_outcomes: Tally::default(),
+        best: Tally::default(),
+    };
+
+    for (opponent, column) in parsed.iter().copied() {
+        let as_move = (game.outcome(column, opponent), game.play(column, opponent));
+
+        let outcome = game.outcome_for(column);
+        let me = game
+            .get_my_move(opponent, outcome)
+            .expect("every outcome should be possible against every move");
+        let as_outcome = (me, outcome, game.play(me, opponent));
+
+        let best = game.best_response(opponent);
+        let best = (
+            best,
+            game.outcome(best, opponent),
+            game.play(best, opponent),
+        );
+
+        analysis.as_moves.record(as_move.0, as_move.1);
+        analysis.as_outcomes.record(as_outcome.1, as_outcome.2);
+        analysis.best.record(best.1, best.2);
+
+        analysis.rounds.push(Round {
+            opponent,
+            column,
+            as_move,
+            as_outcome,
+            best,
+        });
+    }
+
+    analysis
+}
+
+fn print_analysis(game: &Game, analysis: &Analysis) {
+    for (i, round) in analysis.rounds.iter().enumerate() {
+        let Round {
+            opponent,
+            column,
+            as_move,
+            as_outcome,
+            best,
+        } = round;
+
+        println!(
+            "{:>5}: {} {} | as move: {:?} {} | as outcome: {} {:?} {} | best: {} {:?} {}",
+            i + 1,
+            game.opponent_symbol(*opponent),
+            game.my_symbol(*column),
+            as_move.0,
+            as_move.1,
+            game.my_symbol(as_outcome.0),
+            as_outcome.1,
+            as_outcome.2,
+            game.my_symbol(best.0),
+            best.1,
+            best.2,
+        );
+    }
+
+    for (name, tally) in [
+        ("as moves", analysis.as_moves),
+        ("as outcomes", analysis.as_outcomes),
+        ("best", analysis.best),
+    ] {
+        println!(
+            "{name:<12} score {:>7}  W/D/L {}/{}/{}",
+            tally.score, tally.wins, tally.draws, tally.losses
+        );
+    }
+
+    for opponent in game.moves() {
+        let best = game.best_response(opponent);
+
+        println!(
+            "best response to {}: {} ({})",
+            game.opponent_symbol(opponent),
+            game.my_symbol(best),
+            game.play(best, opponent)
+        );
+    }
+}
+
+trait Strategy {
+    fn name(&self) -> String;
+
+    /// `mine` and `theirs` are the moves played so far in the current match.
+    fn choose(&self, game: &Game, mine: &[Move], theirs: &[Move], rng: &mut Rng) -> Move;
+}
+
+/// Picks any move with equal odds.
+struct Random;
+
+impl Strategy for Random {
+    fn name(&self) -> String {
+        String::from("random")
+    }
+
+    fn choose(&self, game: &Game, _: &[Move], _: &[Move], rng: &mut Rng) -> Move {
+        Move(rng.below(game.size()))
+    }
+}
+
+/// Always plays the same move.
+struct Constant(Move);
+
+impl Strategy for Constant {
+    fn name(&self) -> String {
+        format!("always {}", self.0 .0)
+    }
+
+    fn choose(&self, _: &Game, _: &[Move], _: &[Move], _: &mut Rng) -> Move {
+        self.0
+    }
+}
+
+/// Goes through the moves in order.
+struct Cycle;
+
+impl Strategy for Cycle {
+    fn name(&self) -> String {
+        String::from("cycle")
+    }
+
+    fn choose(&self, game: &Game, mine: &[Move], _: &[Move], _: &mut Rng) -> Move {
+        Move(mine.len() % game.size())
+    }
+}
+
+/// Plays the best response to the opponent's last move, assuming they'll repeat it.
+struct Counter;
+
+impl Strategy for Counter {
+    fn name(&self) -> String {
+        String::from("counter last")
+    }
+
+    fn choose(&self, game: &Game, mine: &[Move], theirs: &[Move], rng: &mut Rng) -> Move {
+        match theirs.last() {
+            Some(last) => game.best_response(*last),
+            None => Random.choose(game, mine, theirs, rng),
+        }
+    }
+}
+
+/// Plays the best response to the opponent's most common move so far.
+struct Frequency;
+
+impl Strategy for Frequency {
+    fn name(&self) -> String {
+        String::from("counter most common")
+    }
+
+    fn choose(&self, game: &Game, mine: &[Move], theirs: &[Move], rng: &mut Rng) -> Move {
+        let mut counts = vec![0; game.size()];
+        theirs.iter().for_each(|a_move| counts[a_move.0] += 1);
+
+        let most_common = (0..game.size()).max_by_key(|i| counts[*i]).map(Move);
+
+        match most_common.filter(|_| !theirs.is_empty()) {
+            Some(most_common) => game.best_response(most_common),
+            None => Random.choose(game, mine, theirs, rng),
+        }
+    }
+}
+
+/// Replays a column of the strategy guide, starting over when it runs out.
+struct Guide(&'static str, Vec<Move>);
+
+impl Strategy for Guide {
+    fn name(&self) -> String {
+        String::from(self.0)
+    }
+
+    fn choose(&self, game: &Game, mine: &[Move], theirs: &[Move], rng: &mut Rng) -> Move {
+        match self.1.len() {
+            0 => Random.choose(game, mine, theirs, rng),
+            len => self.1[mine.len() % len],
+        }
+    }
+}
+
+fn default_strategies(game: &Game) -> Vec<Box<dyn Strategy>> {
+    let mut strategies: Vec<Box<dyn Strategy>> = vec![
+        Box::new(Random),
+        Box::new(Cycle),
+        Box::new(Counter),
+        Box::new(Frequency),
+    ];
+
+    strategies.extend(
+        game.moves()
+            .map(|a_move| Box::new(Constant(a_move)) as Box<dyn Strategy>),
+    );
+
+    strategies
+}
+
+fn play_match(
+    game: &Game,
+    a: &dyn Strategy,
+    b: &dyn Strategy,
+    rounds: usize,
+    rng: &mut Rng,
+) -> (Tally, Tally) {
+    let (mut a_moves, mut b_moves) = (vec![], vec![]);
+    let (mut a_tally, mut b_tally) = (Tally::default(), Tally::default());
+
+    for _ in 0..rounds {
+        let a_move = a.choose(game, &a_moves, &b_moves, rng);
+        let b_move = b.choose(game, &b_moves, &a_moves, rng);
+
+        a_tally.record(game.outcome(a_move, b_move), game.play(a_move, b_move));
+        b_tally.record(game.outcome(b_move, a_move), game.play(b_move, a_move));
+
+        a_moves.push(a_move);
+        b_moves.push(b_move);
+    }
+
+    (a_tally, b_tally)
+}
+
+#[derive(Debug, PartialEq)]
+struct Standing {
+    name: String,
+    tally: Tally,
+}
+
+/// Every strategy plays a match of `rounds` rounds against every other one, `tournaments` times
+/// over. The standings are sorted by total score, best first.
+fn tournament(
+    game: &Game,
+    strategies: &[Box<dyn Strategy>],
+    rounds: usize,
+    tournaments: usize,
+    seed: u64,
+) -> Vec<Standing> {
+    let mut rng = Rng::new(seed);
+    let mut tallies = vec![Tally::default(); strategies.len()];
+
+    for _ in 0..tournaments {
+        for a in 0..strategies.len() {
+            for b in a + 1..strategies.len() {
+                let (a_tally, b_tally) =
+                    play_match(game, &*strategies[a], &*strategies[b], rounds, &mut rng);
+
+                for (total, tally) in [(a, a_tally), (b, b_tally)] {
+                    let total = &mut tallies[total];
+                    total.wins += tally.wins;
+                    total.draws += tally.draws;
+                    total.losses += tally.losses;
+                    total.score += tally.score;
+                }
+            }
+        }
+    }
+
+    let mut standings: Vec<Standing> = strategies
+        .iter()
+        .zip(tallies)
+        .map(|(strategy, tally)| Standing {
+            name: strategy.name(),
+            tally,
+        })
+        .collect();
+
+    standings.sort_by_key(|standing| Reverse(standing.tally.score));
+
+    standings
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Part1, Part2};
+    use super::{Constant, Counter, Game, Move, Outcome, Part1, Part2, Strategy, Tally};
 
     const INPUT: &str = r#"
         A Y
@@ -132,17 +669,165 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let parsed = super::parse_input(INPUT);
-        let part_1_ans = super::part_1(&parsed);
+        let game = Game::classic();
+        let parsed = super::parse_input(INPUT, &game).unwrap();
+        let part_1_ans = super::part_1(&game, &parsed);
 
         assert_eq!(part_1_ans, PART_1_TEST_ANS);
     }
 
     #[test]
     fn part_2() {
-        let parsed = super::parse_input(INPUT);
-        let part_2_ans = super::part_2(&parsed);
+        let game = Game::classic();
+        let parsed = super::parse_input(INPUT, &game).unwrap();
+        let part_2_ans = super::part_2(&game, &parsed);
 
         assert_eq!(part_2_ans, PART_2_TEST_ANS);
     }
+
+    #[test]
+    fn classic_rules() {
+        let game = Game::classic();
+        let [rock, paper, scissors] = [Move(0), Move(1), Move(2)];
+
+        assert_eq!(game.outcome(paper, rock), Outcome::Win);
+        assert_eq!(game.outcome(scissors, paper), Outcome::Win);
+        assert_eq!(game.outcome(rock, scissors), Outcome::Win);
+        assert_eq!(game.outcome(rock, paper), Outcome::Loss);
+        assert_eq!(game.outcome(rock, rock), Outcome::Draw);
+    }
+
+    #[test]
+    fn every_move_beats_half_the_others() {
+        let games = [
+            Game::rock_paper_scissors_lizard_spock(),
+            Game::cyclic(5, "ABCDE", "VWXYZ"),
+            Game::cyclic(7, "ABCDEFG", "TUVWXYZ"),
+        ];
+
+        for game in games {
+            for me in game.moves() {
+                let wins = game
+                    .moves()
+                    .filter(|other| game.outcome(me, *other) == Outcome::Win)
+                    .count();
+
+                assert_eq!(wins, game.size() / 2);
+            }
+        }
+    }
+
+    #[test]
+    fn lizard_spock() {
+        let game = Game::rock_paper_scissors_lizard_spock();
+        let parsed = super::parse_input("A Z\nE X\nD V", &game).unwrap();
+
+        // spock vaporizes rock, spock smashes scissors, rock crushes lizard
+        assert_eq!(super::part_1(&game, &parsed), 11 + 3 + 7);
+
+        // when there's a choice, the higher scoring move wins: spock beats rock, draws with
+        // spock and loses to lizard
+        assert_eq!(super::part_2(&game, &parsed), 11 + 8 + 5);
+    }
+
+    #[test]
+    fn custom_scores() {
+        let game = Game::classic()
+            .with_move_scores(vec![0, 0, 0])
+            .with_outcome_scores([0, 1, 2]);
+        let parsed = super::parse_input(INPUT, &game).unwrap();
+
+        // a win, a loss and a draw
+        assert_eq!(super::part_1(&game, &parsed), 3);
+    }
+
+    #[test]
+    fn unknown_symbols_are_errors() {
+        let err = super::parse_input("A Y\nB Q", &Game::classic()).unwrap_err();
+
+        assert_eq!(err.position.line, 2);
+        assert_eq!(err.position.column, 3);
+        assert_eq!(err.expected, "a move symbol");
+
+        let err = super::parse_input("A Y\nQ X", &Game::classic()).unwrap_err();
+
+        assert_eq!(err.position.line, 2);
+        assert_eq!(err.position.column, 1);
+        assert_eq!(err.expected, "a move symbol");
+    }
+
+    #[test]
+    fn blank_lines_are_skipped() {
+        let game = Game::classic();
+        let parsed = super::parse_input("A Y\n\nB X\n", &game).unwrap();
+
+        assert_eq!(parsed, super::parse_input("A Y\nB X", &game).unwrap());
+    }
+
+    #[test]
+    fn analyze() {
+        let game = Game::classic();
+        let parsed = super::parse_input(INPUT, &game).unwrap();
+
+        let analysis = super::analyze(&game, &parsed);
+
+        let one_of_each = |score| Tally {
+            wins: 1,
+            draws: 1,
+            losses: 1,
+            score,
+        };
+
+        assert_eq!(analysis.as_moves, one_of_each(PART_1_TEST_ANS));
+        assert_eq!(analysis.as_outcomes, one_of_each(PART_2_TEST_ANS));
+        assert_eq!(
+            analysis.best,
+            Tally {
+                wins: 3,
+                draws: 0,
+                losses: 0,
+                score: 8 + 9 + 7,
+            }
+        );
+
+        // A Y read as a move is paper against rock, read as an outcome it's a draw
+        assert_eq!(analysis.rounds[0].as_move, (Outcome::Win, 8));
+        assert_eq!(analysis.rounds[0].as_outcome, (Move(0), Outcome::Draw, 4));
+        assert_eq!(analysis.rounds[0].best, (Move(1), Outcome::Win, 8));
+    }
+
+    #[test]
+    fn tournaments_are_reproducible() {
+        let game = Game::classic();
+        let strategies = super::default_strategies(&game);
+
+        let a = super::tournament(&game, &strategies, 50, 10, 42);
+        let b = super::tournament(&game, &strategies, 50, 10, 42);
+
+        assert_eq!(a, b);
+
+        // every strategy plays every other one, 50 rounds at a time, 10 times over
+        for standing in a {
+            let Tally {
+                wins,
+                draws,
+                losses,
+                ..
+            } = standing.tally;
+
+            assert_eq!(wins + draws + losses, (strategies.len() - 1) * 50 * 10);
+        }
+    }
+
+    #[test]
+    fn counter_beats_constant() {
+        let game = Game::classic();
+        let strategies: Vec<Box<dyn Strategy>> =
+            vec![Box::new(Constant(Move(0))), Box::new(Counter)];
+
+        let standings = super::tournament(&game, &strategies, 100, 1, 0);
+
+        assert_eq!(standings[0].name, Counter.name());
+        assert!(standings[0].tally.wins >= 99);
+    }
 }
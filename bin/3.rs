@@ -1,14 +1,26 @@
-use std::{collections::HashSet, fs::read_to_string};
+use advent_2022::{bitset::BitSet64, parse::Position};
+use std::{fmt, fs::read_to_string};
 
 const PROBLEM: u8 = 3;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input = read_to_string(format!("inputs/{PROBLEM}.txt"))?;
 
-    let parsed = parse_input(&input);
+    let arg = |name: &str| {
+        std::env::args()
+            .skip_while(|arg| arg != name)
+            .nth(1)
+            .map(|n| n.parse::<usize>())
+            .transpose()
+    };
 
-    let part_1 = part_1(&parsed);
-    let part_2 = part_2(&parsed);
+    let compartments = arg("--compartments")?.unwrap_or(2);
+    let group_size = arg("--group-size")?.unwrap_or(3);
+
+    let parsed = parse_input(&input, compartments)?;
+
+    let part_1 = part_1(&parsed)?;
+    let part_2 = part_2(&parsed, group_size)?;
 
     println!("Problem {PROBLEM}");
     println!("part-1: {:?}", part_1);
@@ -21,69 +33,221 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 struct Item(char);
 struct Priority(usize);
 
+impl TryFrom<char> for Item {
+    /// The char that isn't an item.
+    type Error = char;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        if c.is_ascii_alphabetic() {
+            Ok(Self(c))
+        } else {
+            Err(c)
+        }
+    }
+}
+
 impl From<&Item> for Priority {
     fn from(item: &Item) -> Self {
-        let inner = match item.0 {
-            c @ 'a'..='z' => (u8::try_from(c).unwrap() - u8::try_from('a').unwrap()) + 1,
-            c @ 'A'..='Z' => (u8::try_from(c).unwrap() - u8::try_from('A').unwrap()) + 27,
-            _ => unreachable!(),
+        // an `Item` is always an ASCII letter, so this can't truncate
+        let c = item.0 as u8;
+
+        let inner = if c.is_ascii_lowercase() {
+            c - b'a' + 1
+        } else {
+            c - b'A' + 27
         } as usize;
 
         Self(inner)
     }
 }
 
-type Parsed = Vec<(HashSet<Item>, HashSet<Item>)>;
+#[derive(Debug, Clone, PartialEq)]
+struct Rucksack {
+    line: usize,
+    /// The priorities of the items in each compartment.
+    compartments: Vec<BitSet64>,
+}
+
+impl Rucksack {
+    fn items(&self) -> BitSet64 {
+        self.compartments
+            .iter()
+            .copied()
+            .fold(BitSet64::default(), |a, b| a | b)
+    }
+}
+
+type Parsed = Vec<Rucksack>;
 
 type Part1 = usize;
 type Part2 = usize;
 
-fn parse_input(input: &str) -> Parsed {
+#[derive(Debug, PartialEq)]
+enum Error {
+    /// Rucksacks need at least one compartment.
+    NoCompartments,
+    /// Groups need at least one rucksack.
+    EmptyGroups,
+    /// The rucksack's items can't be split evenly between its compartments.
+    Uneven {
+        line: usize,
+        items: usize,
+        compartments: usize,
+    },
+    /// The last group is missing some rucksacks. `line` is where the group starts.
+    IncompleteGroup {
+        line: usize,
+        rucksacks: usize,
+        group_size: usize,
+    },
+    InvalidItem {
+        position: Position,
+        item: char,
+    },
+    NoCommonItem {
+        line: usize,
+    },
+    MultipleCommonItems {
+        line: usize,
+        priorities: Vec<usize>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoCompartments => write!(f, "a rucksack needs at least one compartment"),
+            Error::EmptyGroups => write!(f, "a group needs at least one rucksack"),
+            Error::Uneven {
+                line,
+                items,
+                compartments,
+            } => write!(
+                f,
+                "line {line}: {items} items can't be split into {compartments} compartments"
+            ),
+            Error::IncompleteGroup {
+                line,
+                rucksacks,
+                group_size,
+            } => write!(
+                f,
+                "line {line}: group has {rucksacks} rucksacks, expected {group_size}"
+            ),
+            Error::InvalidItem { position, item } => {
+                write!(f, "{position}: {item:?} isn't an item")
+            }
+            Error::NoCommonItem { line } => write!(f, "line {line}: no item in common"),
+            Error::MultipleCommonItems { line, priorities } => write!(
+                f,
+                "line {line}: expected one item in common, found priorities {priorities:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn parse_input(input: &str, compartments: usize) -> Result<Parsed, Error> {
+    if compartments == 0 {
+        return Err(Error::NoCompartments);
+    }
+
     input
         .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .map(|line| line.split_at(line.len() / 2))
-        .map(|(left, right)| {
-            (
-                left.chars().map(Item).collect(),
-                right.chars().map(Item).collect(),
-            )
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_number, line)| {
+            let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+
+            let priorities = line
+                .trim()
+                .chars()
+                .enumerate()
+                .map(|(i, c)| {
+                    let item = Item::try_from(c).map_err(|item| Error::InvalidItem {
+                        position: Position {
+                            line: line_number,
+                            column: indent + i + 1,
+                        },
+                        item,
+                    })?;
+
+                    Ok(Priority::from(&item).0)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if priorities.len() % compartments != 0 {
+                return Err(Error::Uneven {
+                    line: line_number,
+                    items: priorities.len(),
+                    compartments,
+                });
+            }
+
+            // split by item rather than by byte, which could land inside a char
+            let size = priorities.len() / compartments;
+
+            Ok(Rucksack {
+                line: line_number,
+                compartments: priorities
+                    .chunks(size)
+                    .map(|compartment| compartment.iter().copied().collect())
+                    .collect(),
+            })
         })
         .collect()
 }
 
-fn part_1(parsed: &Parsed) -> Part1 {
+/// The priority of the one item in all of `sets`.
+fn common_item(sets: impl IntoIterator<Item = BitSet64>, line: usize) -> Result<usize, Error> {
+    let common = sets
+        .into_iter()
+        .reduce(|common, set| common & set)
+        .unwrap_or_default();
+
+    match common.len() {
+        0 => Err(Error::NoCommonItem { line }),
+        1 => Ok(common.iter().sum()),
+        _ => Err(Error::MultipleCommonItems {
+            line,
+            priorities: common.iter().collect(),
+        }),
+    }
+}
+
+fn part_1(parsed: &Parsed) -> Result<Part1, Error> {
     parsed
         .iter()
-        .flat_map(|(left, right)| left.intersection(right))
-        .map(Priority::from)
-        .map(|p| p.0)
+        .map(|rucksack| common_item(rucksack.compartments.iter().copied(), rucksack.line))
         .sum()
 }
 
-fn part_2(parsed: &Parsed) -> Part2 {
-    let lines: Vec<HashSet<&Item>> = parsed
-        .iter()
-        .map(|(left, right)| HashSet::from_iter(left.union(right)))
-        .collect();
-
-    lines
-        .chunks(3)
-        .flat_map(|chunks| {
-            HashSet::from_iter(chunks[0].intersection(&chunks[1]).copied())
-                .intersection(&chunks[2])
-                .copied()
-                .collect::<Vec<&Item>>()
-        })
-        .map(Priority::from)
-        .map(|p| p.0)
+fn part_2(parsed: &Parsed, group_size: usize) -> Result<Part2, Error> {
+    if group_size == 0 {
+        return Err(Error::EmptyGroups);
+    }
+
+    let rest = parsed.len() % group_size;
+
+    if rest != 0 {
+        return Err(Error::IncompleteGroup {
+            line: parsed[parsed.len() - rest].line,
+            rucksacks: rest,
+            group_size,
+        });
+    }
+
+    parsed
+        .chunks(group_size)
+        .map(|group| common_item(group.iter().map(Rucksack::items), group[0].line))
         .sum()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Part1, Part2};
+    use super::{Error, Part1, Part2, Position};
 
     const INPUT: &str = r#"
 vJrwpWtwJgWrhcsFMMfFFhFp
@@ -99,17 +263,131 @@ CrZsJsPPZsGzwwsLwLmpwMDw
 
     #[test]
     fn part_1() {
-        let parsed = super::parse_input(INPUT);
-        let part_1_ans = super::part_1(&parsed);
+        let parsed = super::parse_input(INPUT, 2).unwrap();
+        let part_1_ans = super::part_1(&parsed).unwrap();
 
         assert_eq!(part_1_ans, PART_1_TEST_ANS);
     }
 
     #[test]
     fn part_2() {
-        let parsed = super::parse_input(INPUT);
-        let part_2_ans = super::part_2(&parsed);
+        let parsed = super::parse_input(INPUT, 2).unwrap();
+        let part_2_ans = super::part_2(&parsed, 3).unwrap();
 
         assert_eq!(part_2_ans, PART_2_TEST_ANS);
     }
+
+    #[test]
+    fn three_compartments() {
+        // `a` is in all three compartments, `b` only in two
+        let parsed = super::parse_input("abxacyazb", 3).unwrap();
+
+        assert_eq!(parsed[0].compartments.len(), 3);
+        assert_eq!(super::part_1(&parsed), Ok(1));
+    }
+
+    #[test]
+    fn groups_of_two() {
+        let parsed = super::parse_input("abcd\nefcg\nhizj\nklzm", 2).unwrap();
+
+        assert_eq!(super::part_2(&parsed, 2), Ok(3 + 26));
+    }
+
+    #[test]
+    fn uneven_rucksack() {
+        let err = super::parse_input(
+            INPUT
+                .replace("ttgJtRGJQctTZtZT", "ttgJtRGJQctTZtZ")
+                .as_str(),
+            2,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::Uneven {
+                line: 6,
+                items: 15,
+                compartments: 2
+            }
+        );
+    }
+
+    #[test]
+    fn incomplete_group() {
+        let parsed = super::parse_input(INPUT, 2).unwrap();
+
+        let err = super::part_2(&parsed, 4).unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::IncompleteGroup {
+                line: 6,
+                rucksacks: 2,
+                group_size: 4
+            }
+        );
+    }
+
+    #[test]
+    fn zero_sizes() {
+        assert_eq!(super::parse_input(INPUT, 0), Err(Error::NoCompartments));
+
+        let parsed = super::parse_input(INPUT, 2).unwrap();
+        assert_eq!(super::part_2(&parsed, 0), Err(Error::EmptyGroups));
+    }
+
+    #[test]
+    fn common_items() {
+        let parsed = super::parse_input("abcd\nabef", 2).unwrap();
+
+        assert_eq!(super::part_1(&parsed), Err(Error::NoCommonItem { line: 1 }));
+        assert_eq!(
+            super::part_2(&parsed, 2),
+            Err(Error::MultipleCommonItems {
+                line: 1,
+                priorities: vec![1, 2]
+            })
+        );
+    }
+
+    #[test]
+    fn items() {
+        assert_eq!(super::Item::try_from('a'), Ok(super::Item('a')));
+        assert_eq!(super::Item::try_from('Z'), Ok(super::Item('Z')));
+        assert_eq!(super::Item::try_from('é'), Err('é'));
+        assert_eq!(super::Item::try_from('1'), Err('1'));
+    }
+
+    #[test]
+    fn invalid_items_are_errors() {
+        let input = INPUT.replace("ttgJtRGJQctTZtZT", "ttgJtRGJQ\u{e9}tTZtZT");
+
+        let err = super::parse_input(&input, 2).unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::InvalidItem {
+                position: Position {
+                    line: 6,
+                    column: 10
+                },
+                item: '\u{e9}'
+            }
+        );
+    }
+
+    #[test]
+    fn columns_count_chars_not_bytes() {
+        // an ideographic space is three bytes long
+        let err = super::parse_input("\u{3000}ab!", 2).unwrap_err();
+
+        assert_eq!(
+            err,
+            Error::InvalidItem {
+                position: Position { line: 1, column: 4 },
+                item: '!'
+            }
+        );
+    }
 }
@@ -1,11 +1,14 @@
-use std::{convert::Infallible, fs::read_to_string, str::FromStr};
+use advent_2022::parse::{
+    self, lines, pair, preceded, spaces, tag, terminated, try_map, unsigned, Input, PResult, Parser,
+};
+use std::{fmt, fs::read_to_string};
 
 const PROBLEM: u8 = 4;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input = read_to_string(format!("inputs/{PROBLEM}.txt"))?;
 
-    let parsed = parse_input(&input);
+    let parsed = parse_input(&input)?;
 
     let part_1 = part_1(&parsed);
     let part_2 = part_2(&parsed);
@@ -14,22 +17,50 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("part-1: {:?}", part_1);
     println!("part-2: {:?}", part_2);
 
+    if std::env::args().any(|arg| arg == "--pairs") {
+        for (left, right) in &parsed {
+            let Coverage { both, neither } = coverage(left, right);
+            let show = |range: Option<Range>| range.map_or(String::from("-"), |r| r.to_string());
+
+            println!(
+                "{left},{right}: overlap {} sections, union {}, both {}, neither {}",
+                left.overlap_len(right),
+                show(left.union(right)),
+                show(both),
+                show(neither),
+            );
+        }
+    }
+
+    if std::env::args().any(|arg| arg == "--all-pairs") {
+        let elves = overlapping_elves(&parsed);
+        println!("elves overlapping another elf: {}", elves.len());
+    }
+
     Ok(())
 }
 
-#[derive(Debug)]
+/// An inclusive range of sections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Range(usize, usize);
 
-impl FromStr for Range {
-    type Err = Infallible;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (left, right) = s.trim().split_once('-').unwrap();
-        Ok(Self(left.parse().unwrap(), right.parse().unwrap()))
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.0, self.1)
     }
 }
 
 impl Range {
+    /// `start-end`, where the start can't be after the end.
+    fn parse(input: Input<'_>) -> PResult<'_, Self> {
+        try_map(
+            pair(terminated(unsigned(), tag("-")), unsigned()),
+            "a range that doesn't end before it starts",
+            |(start, end)| (start <= end).then_some(Self(start, end)),
+        )
+        .parse(input)
+    }
+
     fn contains(&self, other: &Self) -> bool {
         self.0 <= other.0 && self.1 >= other.1
     }
@@ -39,6 +70,93 @@ impl Range {
             || other.0 <= self.1 && self.1 <= other.1
             || self.0 < other.0 && self.1 > other.1
     }
+
+    fn len(&self) -> usize {
+        self.1 - self.0 + 1
+    }
+
+    /// The sections in both ranges.
+    fn overlap(&self, other: &Self) -> Option<Self> {
+        let start = self.0.max(other.0);
+        let end = self.1.min(other.1);
+
+        (start <= end).then_some(Self(start, end))
+    }
+
+    fn overlap_len(&self, other: &Self) -> usize {
+        self.overlap(other).map_or(0, |overlap| overlap.len())
+    }
+
+    /// The sections in either range, if there's no gap between them.
+    fn union(&self, other: &Self) -> Option<Self> {
+        let is_contiguous = other.0 <= self.1 + 1 && self.0 <= other.1 + 1;
+
+        is_contiguous.then_some(Self(self.0.min(other.0), self.1.max(other.1)))
+    }
+
+    /// The sections between the two ranges that neither of them covers.
+    fn gap(&self, other: &Self) -> Option<Self> {
+        let (first, second) = if self.0 <= other.0 {
+            (self, other)
+        } else {
+            (other, self)
+        };
+
+        (first.1 + 1 < second.0).then_some(Self(first.1 + 1, second.0 - 1))
+    }
+}
+
+/// How a pair of elves covers the sections from the first one either of them has to the last.
+#[derive(Debug, PartialEq, Eq)]
+struct Coverage {
+    both: Option<Range>,
+    neither: Option<Range>,
+}
+
+fn coverage(left: &Range, right: &Range) -> Coverage {
+    Coverage {
+        both: left.overlap(right),
+        neither: left.gap(right),
+    }
+}
+
+/// Every elf whose assignment overlaps any other elf's, across all the pairs. The elves of pair
+/// `i` are `2 * i` and `2 * i + 1`.
+///
+/// Sweeps through the assignments by where they start, keeping track of the one that reaches the
+/// furthest so far: anything starting before that ends overlaps it.
+fn overlapping_elves(parsed: &Parsed) -> Vec<usize> {
+    let mut assignments: Vec<(Range, usize)> = parsed
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (left, right))| [(*left, 2 * i), (*right, 2 * i + 1)])
+        .collect();
+
+    assignments.sort_unstable_by_key(|(range, _)| range.0);
+
+    let mut is_overlapping = vec![false; assignments.len()];
+    let mut furthest: Option<(usize, usize)> = None;
+
+    for (range, elf) in assignments {
+        match furthest {
+            Some((end, other)) if range.0 <= end => {
+                is_overlapping[elf] = true;
+                is_overlapping[other] = true;
+
+                if range.1 > end {
+                    furthest = Some((range.1, elf));
+                }
+            }
+            _ => furthest = Some((range.1, elf)),
+        }
+    }
+
+    is_overlapping
+        .into_iter()
+        .enumerate()
+        .filter(|(_, is_overlapping)| *is_overlapping)
+        .map(|(elf, _)| elf)
+        .collect()
 }
 
 type Parsed = Vec<(Range, Range)>;
@@ -46,18 +164,10 @@ type Parsed = Vec<(Range, Range)>;
 type Part1 = usize;
 type Part2 = usize;
 
-fn parse_input(input: &str) -> Parsed {
-    input
-        .trim()
-        .lines()
-        .filter_map(|line| line.split_once(','))
-        .map(|(left, right)| {
-            (
-                Range::from_str(left).unwrap(),
-                Range::from_str(right).unwrap(),
-            )
-        })
-        .collect()
+fn parse_input(input: &str) -> Result<Parsed, parse::Error> {
+    let assignments = pair(terminated(Range::parse, tag(",")), Range::parse);
+
+    parse::parse(input, lines(preceded(spaces, assignments)))
 }
 
 fn part_1(parsed: &Parsed) -> Part1 {
@@ -76,7 +186,9 @@ fn part_2(parsed: &Parsed) -> Part2 {
 
 #[cfg(test)]
 mod tests {
-    use super::{Part1, Part2};
+    use super::{Coverage, Part1, Part2, Range};
+    use advent_2022::parse::Position;
+    use advent_2022::rng::Rng;
 
     const INPUT: &str = r#"
 2-4,6-8
@@ -92,7 +204,7 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let parsed = super::parse_input(INPUT);
+        let parsed = super::parse_input(INPUT).unwrap();
         let part_1_ans = super::part_1(&parsed);
 
         assert_eq!(part_1_ans, PART_1_TEST_ANS);
@@ -100,9 +212,97 @@ mod tests {
 
     #[test]
     fn part_2() {
-        let parsed = super::parse_input(INPUT);
+        let parsed = super::parse_input(INPUT).unwrap();
         let part_2_ans = super::part_2(&parsed);
 
         assert_eq!(part_2_ans, PART_2_TEST_ANS);
     }
+
+    #[test]
+    fn overlap_and_union() {
+        assert_eq!(Range(5, 7).overlap(&Range(7, 9)), Some(Range(7, 7)));
+        assert_eq!(Range(2, 8).overlap_len(&Range(3, 7)), 5);
+        assert_eq!(Range(2, 3).overlap_len(&Range(4, 5)), 0);
+
+        assert_eq!(Range(2, 3).union(&Range(4, 5)), Some(Range(2, 5)));
+        assert_eq!(Range(6, 6).union(&Range(4, 6)), Some(Range(4, 6)));
+        assert_eq!(Range(2, 4).union(&Range(6, 8)), None);
+    }
+
+    #[test]
+    fn coverage() {
+        assert_eq!(
+            super::coverage(&Range(2, 4), &Range(6, 8)),
+            Coverage {
+                both: None,
+                neither: Some(Range(5, 5))
+            }
+        );
+        assert_eq!(
+            super::coverage(&Range(4, 8), &Range(2, 6)),
+            Coverage {
+                both: Some(Range(4, 6)),
+                neither: None
+            }
+        );
+    }
+
+    #[test]
+    fn overlapping_elves() {
+        let parsed = super::parse_input(INPUT).unwrap();
+
+        // 2-4 and 6-8 don't overlap each other, but do overlap 2-3 and 5-7 from other pairs
+        assert_eq!(
+            super::overlapping_elves(&parsed),
+            (0..12).collect::<Vec<_>>()
+        );
+
+        let parsed = vec![(Range(1, 2), Range(4, 5)), (Range(5, 6), Range(8, 9))];
+        assert_eq!(super::overlapping_elves(&parsed), [1, 2]);
+    }
+
+    #[test]
+    fn overlapping_elves_matches_checking_every_pair() {
+        let mut rng = Rng::new(4);
+
+        let range = |rng: &mut Rng| {
+            let start = rng.below(1000);
+            Range(start, start + rng.below(5))
+        };
+
+        let parsed: super::Parsed = (0..300)
+            .map(|_| (range(&mut rng), range(&mut rng)))
+            .collect();
+
+        let assignments: Vec<Range> = parsed.iter().flat_map(|(l, r)| [*l, *r]).collect();
+        let expected: Vec<usize> = (0..assignments.len())
+            .filter(|a| {
+                (0..assignments.len()).any(|b| *a != b && assignments[*a].overlaps(&assignments[b]))
+            })
+            .collect();
+
+        assert_eq!(super::overlapping_elves(&parsed), expected);
+    }
+
+    #[test]
+    fn invalid_assignments_are_errors() {
+        let err = |line: &str| super::parse_input(&INPUT.replace("5-7,7-9", line)).unwrap_err();
+
+        let reversed = err("5-7,9-7");
+        assert_eq!(reversed.position, Position { line: 4, column: 5 });
+        assert_eq!(
+            reversed.expected,
+            "a range that doesn't end before it starts"
+        );
+
+        let missing_comma = err("5-7 7-9");
+        assert_eq!(missing_comma.position, Position { line: 4, column: 4 });
+        assert_eq!(missing_comma.expected, r#"",""#);
+
+        let not_a_number = err("5-x,7-9");
+        assert_eq!(not_a_number.position, Position { line: 4, column: 3 });
+
+        let extra_field = err("5-7,7-9,1-2");
+        assert_eq!(extra_field.position, Position { line: 4, column: 8 });
+    }
 }
@@ -1,115 +1,155 @@
-use std::{convert::Infallible, fs::read_to_string, str::FromStr};
+use advent_2022::parse::{
+    self, blank_lines, line, many, map, pair, preceded, separated, spaces, tag, take_while1,
+    terminated, unsigned, Input, PResult, Parser, Position,
+};
+use advent_2022::stacks::{move_in_order, move_reversed};
+use std::{fmt, fs::read_to_string, ops::Range};
 
 const PROBLEM: u8 = 5;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input = read_to_string(format!("inputs/{PROBLEM}.txt"))?;
 
-    let parsed = parse_input(&input);
+    let parsed = parse_input(&input)?;
 
-    let part_1 = part_1(parsed.clone());
-    let part_2 = part_2(parsed);
+    if std::env::args().any(|arg| arg == "--dry-run") {
+        dry_run(&parsed.0, &parsed.1)?;
+        println!("all {} moves can be made", parsed.1.len());
+    }
+
+    // e.g. `--crane batch-3`
+    let crane = std::env::args()
+        .skip_while(|arg| arg != "--crane")
+        .nth(1)
+        .map(|name| crane(&name).ok_or(format!("unknown crane {name:?}")))
+        .transpose()?;
+
+    let part_1 = part_1(parsed.clone())?;
+    let part_2 = part_2(parsed.clone())?;
 
     println!("Problem {PROBLEM}");
     println!("part-1: {:?}", part_1);
     println!("part-2: {:?}", part_2);
 
-    Ok(())
-}
+    let crane = crane.unwrap_or_else(|| Box::new(CrateMover9000));
 
-#[derive(Debug)]
-struct Crate(Option<char>);
+    let mut replay = Replay::new(&*crane, parsed.0.clone(), &parsed.1);
+    let arg = |name: &str, n: usize| std::env::args().skip_while(|arg| arg != name).nth(n);
+
+    if std::env::args().any(|arg| arg == "--frames") {
+        print!("{}", replay.frames()?);
+    }
 
-impl FromStr for Crate {
-    type Err = &'static str;
+    if let Some(position) = arg("--jump", 1) {
+        replay.jump(position.parse()?)?;
+        println!("after {} moves:\n{}", replay.position(), replay.stacks());
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 3 {
-            return Err("pass in the proper size string");
+    // e.g. `--arrival D 3` for the first move that puts a D on stack 3
+    if let (Some(label), Some(stack)) = (arg("--arrival", 1), arg("--arrival", 2)) {
+        match replay.first_arrival(&label, stack.parse()?)? {
+            Some(position) => println!("{label} reaches stack {stack} on move {position}"),
+            None => println!("{label} never reaches stack {stack}"),
         }
+    }
 
-        let mut chars = s.chars();
-
-        chars.next();
+    if std::env::args().any(|arg| arg == "--crane") {
+        println!("{}: {:?}", crane.name(), run(&*crane, parsed)?);
+    }
 
-        let inner = chars.next().filter(|c| *c != ' ');
+    Ok(())
+}
 
-        Ok(Self(inner))
-    }
+/// A crate in the drawing, along with where it was drawn.
+#[derive(Debug)]
+struct Crate {
+    label: String,
+    position: Position,
+    columns: Range<usize>,
 }
 
 impl Crate {
-    fn take_from_str(s: &str) -> (Self, &str) {
-        if s.len() > 3 {
-            (s[0..3].parse().unwrap(), &s[4..])
-        } else {
-            (s[0..3].parse().unwrap(), "")
-        }
+    /// A label in brackets. Labels can be any length, so cells aren't limited to 3 chars wide.
+    fn parse(input: Input<'_>) -> PResult<'_, Self> {
+        let label = take_while1("a crate label", |c| {
+            !c.is_whitespace() && c != '[' && c != ']'
+        });
+
+        let (rest, label) = preceded(tag("["), terminated(label, tag("]"))).parse(input)?;
+
+        let a_crate = Self {
+            label,
+            position: input.position(),
+            columns: input.position().column..rest.position().column,
+        };
+
+        Ok((rest, a_crate))
     }
 }
 
+/// A row of the drawing. Crates are placed by which stack number they're above, so any amount
+/// of space between them (or after them) is fine.
 struct Crates(Vec<Crate>);
 
-impl FromStr for Crates {
-    type Err = Infallible;
+impl Crates {
+    fn parse(input: Input<'_>) -> PResult<'_, Self> {
+        map(line(many(preceded(spaces, Crate::parse))), Self).parse(input)
+    }
+}
 
-    fn from_str(mut s: &str) -> Result<Self, Self::Err> {
-        let mut crates = vec![];
+/// A stack number from the footer of the drawing.
+struct StackNumber {
+    number: usize,
+    position: Position,
+    columns: Range<usize>,
+}
 
-        while !s.is_empty() {
-            let (one_crate, rest) = Crate::take_from_str(s);
-            crates.push(one_crate);
-            s = rest;
-        }
+impl StackNumber {
+    fn parse(input: Input<'_>) -> PResult<'_, Self> {
+        let (rest, number) = unsigned().parse(input)?;
 
-        Ok(Self(crates))
+        let stack_number = Self {
+            number,
+            position: input.position(),
+            columns: input.position().column..rest.position().column,
+        };
+
+        Ok((rest, stack_number))
     }
 }
 
-#[derive(Clone, Debug)]
-struct Stack(Vec<char>);
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Stack(Vec<String>);
 
 impl Stack {
     fn new() -> Self {
         Self(vec![])
     }
 
-    fn push(&mut self, c: char) {
-        self.0.push(c);
-    }
-
-    fn pop(&mut self) -> Option<char> {
-        self.0.pop()
+    fn push(&mut self, label: String) {
+        self.0.push(label);
     }
 
-    fn peak(&self) -> Option<&char> {
-        self.0.last()
+    fn peak(&self) -> Option<&str> {
+        self.0.last().map(String::as_str)
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 struct Stacks(Vec<Stack>);
 
 impl Stacks {
-    fn move_crates_one_at_a_time(&mut self, a_move: Move) {
-        for _ in 0..a_move.count {
-            let a_crate = self.0[a_move.from - 1].pop().unwrap();
-            self.0[a_move.to - 1].push(a_crate)
-        }
+    fn heights(&self) -> Vec<usize> {
+        self.0.iter().map(|stack| stack.0.len()).collect()
     }
 
-    fn move_crates_together(&mut self, a_move: Move) {
-        let mut crates = vec![];
-
-        for _ in 0..a_move.count {
-            let a_crate = self.0[a_move.from - 1].pop().unwrap();
-            crates.push(a_crate);
-        }
+    fn check(&self, a_move: &Move) -> Result<(usize, usize), MoveError> {
+        a_move.check(self.0.len(), |i| self.0[i].0.len())
+    }
 
-        crates
-            .into_iter()
-            .rev()
-            .for_each(|a_crate| self.0[a_move.to - 1].push(a_crate));
+    /// The crate on top of each stack, skipping empty ones.
+    fn tops(&self) -> String {
+        self.0.iter().filter_map(Stack::peak).collect()
     }
 }
 
@@ -125,11 +165,50 @@ impl std::fmt::Debug for Stacks {
     }
 }
 
+/// The drawing from the puzzle, top down, with the footer of stack numbers on the last line.
+///
+/// Cells are all as wide as the widest crate or stack number, so the drawing always parses back
+/// into the same stacks.
+impl fmt::Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .0
+            .iter()
+            .flat_map(|stack| stack.0.iter().map(|label| label.chars().count() + 2))
+            .chain([3, self.0.len().to_string().len()])
+            .max()
+            .unwrap_or_default();
+
+        let height = self.0.iter().map(|stack| stack.0.len()).max().unwrap_or(0);
+
+        for level in (0..height).rev() {
+            let cells: Vec<String> = self
+                .0
+                .iter()
+                .map(|stack| match stack.0.get(level) {
+                    Some(label) => format!("{:^width$}", format!("[{label}]")),
+                    None => " ".repeat(width),
+                })
+                .collect();
+
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+
+        let footer: Vec<String> = (1..=self.0.len())
+            .map(|number| format!("{number:^width$}"))
+            .collect();
+
+        write!(f, "{}", footer.join(" "))
+    }
+}
+
 #[derive(Clone)]
 struct Move {
     count: usize,
     from: usize,
     to: usize,
+    /// Where the move is in the input, for error messages.
+    line: usize,
 }
 
 impl std::fmt::Debug for Move {
@@ -138,88 +217,389 @@ impl std::fmt::Debug for Move {
     }
 }
 
-impl FromStr for Move {
-    type Err = Infallible;
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.count, self.from, self.to)
+    }
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut iter = s.split(' ').filter_map(|c| usize::from_str(c).ok());
+impl Move {
+    fn parse(input: Input<'_>) -> PResult<'_, Self> {
+        let count = preceded(tag("move "), unsigned());
+        let from = preceded(tag(" from "), unsigned());
+        let to = preceded(tag(" to "), unsigned());
+
+        let line_number = input.position().line;
+
+        map(
+            line(pair(count, pair(from, to))),
+            move |(count, (from, to))| Self {
+                count,
+                from,
+                to,
+                line: line_number,
+            },
+        )
+        .parse(input)
+    }
 
-        Ok(Self {
-            count: iter.next().unwrap(),
-            from: iter.next().unwrap(),
-            to: iter.next().unwrap(),
-        })
+    /// Whether the move can be made on `stacks` stacks, where stack `i` (counting from 0) is
+    /// `height(i)` crates tall, and if so which stacks it moves crates between.
+    fn check(
+        &self,
+        stacks: usize,
+        height: impl Fn(usize) -> usize,
+    ) -> Result<(usize, usize), MoveError> {
+        let index = |stack: usize| {
+            (1..=stacks)
+                .contains(&stack)
+                .then(|| stack - 1)
+                .ok_or(MoveError::NoSuchStack {
+                    line: self.line,
+                    stack,
+                    stacks,
+                })
+        };
+
+        let (from, to) = (index(self.from)?, index(self.to)?);
+
+        if height(from) < self.count {
+            return Err(MoveError::NotEnoughCrates {
+                line: self.line,
+                stack: self.from,
+                height: height(from),
+                count: self.count,
+            });
+        }
+
+        Ok((from, to))
     }
 }
 
-type Parsed = (Stacks, Vec<Move>);
+#[derive(Debug, PartialEq, Eq)]
+enum MoveError {
+    NoSuchStack {
+        line: usize,
+        stack: usize,
+        stacks: usize,
+    },
+    /// The stack has `height` crates on it, but the move needs `count`.
+    NotEnoughCrates {
+        line: usize,
+        stack: usize,
+        height: usize,
+        count: usize,
+    },
+}
 
-type Part1 = String;
-type Part2 = String;
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::NoSuchStack {
+                line,
+                stack,
+                stacks,
+            } => write!(
+                f,
+                "line {line}: there is no stack {stack}, only 1 to {stacks}"
+            ),
+            MoveError::NotEnoughCrates {
+                line,
+                stack,
+                height,
+                count,
+            } => write!(
+                f,
+                "line {line}: can't move {count} crates from stack {stack}, which has {height}"
+            ),
+        }
+    }
+}
 
-fn parse_input(input: &str) -> Parsed {
-    let (stacks_raw, moves_raw) = input.split_once("\n\n").unwrap();
-
-    let stacks = {
-        let mut lines: Vec<&str> = stacks_raw.lines().filter(|line| !line.is_empty()).collect();
-        let col_count = lines
-            .pop()
-            .unwrap()
-            .trim()
-            .chars()
-            .filter(|c| c.is_ascii_digit())
-            .last()
-            .and_then(|c| c.to_digit(10))
-            .unwrap() as usize;
-
-        let rows = lines
-            .into_iter()
-            .map(|line| Crates::from_str(line).unwrap());
-
-        let mut stacks = vec![Stack::new(); col_count];
-
-        rows.rev().for_each(|row| {
-            row.0.into_iter().enumerate().for_each(|(col, val)| {
-                if let Crate(Some(c)) = val {
-                    stacks[col].push(c)
-                }
-            })
-        });
+impl std::error::Error for MoveError {}
+
+trait Crane {
+    fn name(&self) -> String;
+
+    /// Moves `count` crates from the top of stack `from` to stack `to`, counting from 0. The
+    /// move has already been checked, so `from` has at least `count` crates on it.
+    fn move_crates(&self, stacks: &mut [Stack], from: usize, to: usize, count: usize);
 
-        Stacks(stacks)
-    };
+    fn apply(&self, stacks: &mut Stacks, a_move: &Move) -> Result<(), MoveError> {
+        let (from, to) = stacks.check(a_move)?;
 
-    let moves = moves_raw
-        .trim()
-        .lines()
-        .map(|line| Move::from_str(line).unwrap())
-        .collect();
+        self.move_crates(&mut stacks.0, from, to, a_move.count);
 
-    (stacks, moves)
+        Ok(())
+    }
 }
 
-fn part_1(parsed: Parsed) -> Part1 {
-    let (mut stacks, moves) = parsed;
+/// Moves one crate at a time, so the crates end up in reverse order.
+struct CrateMover9000;
 
-    for a_move in moves {
-        stacks.move_crates_one_at_a_time(a_move);
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9000")
+    }
+
+    fn move_crates(&self, stacks: &mut [Stack], from: usize, to: usize, count: usize) {
+        // moving crates onto the stack they came from leaves it as it was
+        let Ok([from, to]) = stacks.get_disjoint_mut([from, to]) else {
+            return;
+        };
+
+        move_reversed(&mut from.0, &mut to.0, count);
+    }
+}
+
+/// Moves all the crates at once, so they keep their order.
+struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        String::from("CrateMover 9001")
     }
 
-    stacks.0.iter().filter_map(|stack| stack.peak()).collect()
+    fn move_crates(&self, stacks: &mut [Stack], from: usize, to: usize, count: usize) {
+        let Ok([from, to]) = stacks.get_disjoint_mut([from, to]) else {
+            return;
+        };
+
+        move_in_order(&mut from.0, &mut to.0, count);
+    }
 }
 
-fn part_2(parsed: Parsed) -> Part2 {
+/// Can only lift so many crates at once, so bigger moves are made in batches. Each batch keeps
+/// its order, like the 9001.
+struct Batched(usize);
+
+impl Crane for Batched {
+    fn name(&self) -> String {
+        format!("batches of {}", self.0)
+    }
+
+    fn move_crates(&self, stacks: &mut [Stack], from: usize, to: usize, count: usize) {
+        let mut remaining = count;
+
+        while remaining > 0 {
+            let batch = remaining.min(self.0);
+            CrateMover9001.move_crates(stacks, from, to, batch);
+            remaining -= batch;
+        }
+    }
+}
+
+/// `9000`, `9001`, or `batch-K` for a crane that moves up to `K` crates at a time.
+fn crane(name: &str) -> Option<Box<dyn Crane>> {
+    match name {
+        "9000" => Some(Box::new(CrateMover9000)),
+        "9001" => Some(Box::new(CrateMover9001)),
+        _ => {
+            let size = name.strip_prefix("batch-")?.parse().ok()?;
+            (size > 0).then(|| Box::new(Batched(size)) as Box<dyn Crane>)
+        }
+    }
+}
+
+/// Makes every move with `crane`, then reads off the top of each stack.
+fn run(crane: &dyn Crane, parsed: Parsed) -> Result<String, MoveError> {
     let (mut stacks, moves) = parsed;
 
+    for a_move in &moves {
+        crane.apply(&mut stacks, a_move)?;
+    }
+
+    Ok(stacks.tops())
+}
+
+/// Plays the moves forwards and backwards.
+///
+/// Only the crates taken off the top of a stack are recorded for each move, which is all it takes
+/// to undo it, whatever the crane did with them.
+struct Replay<'a> {
+    crane: &'a dyn Crane,
+    moves: &'a [Move],
+    stacks: Stacks,
+    /// The crates each move made so far took, in the order they were stacked in.
+    history: Vec<Vec<String>>,
+}
+
+impl<'a> Replay<'a> {
+    fn new(crane: &'a dyn Crane, stacks: Stacks, moves: &'a [Move]) -> Self {
+        Self {
+            crane,
+            moves,
+            stacks,
+            history: vec![],
+        }
+    }
+
+    fn stacks(&self) -> &Stacks {
+        &self.stacks
+    }
+
+    /// How many moves have been made.
+    fn position(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Makes the next move, if there is one.
+    fn step(&mut self) -> Result<bool, MoveError> {
+        let Some(a_move) = self.moves.get(self.position()) else {
+            return Ok(false);
+        };
+
+        let (from, _) = self.stacks.check(a_move)?;
+        let stack = &self.stacks.0[from].0;
+        let taken = stack[stack.len() - a_move.count..].to_vec();
+
+        self.crane.apply(&mut self.stacks, a_move)?;
+        self.history.push(taken);
+
+        Ok(true)
+    }
+
+    /// Undoes the last move, if there is one.
+    fn back(&mut self) -> bool {
+        let Some(taken) = self.history.pop() else {
+            return false;
+        };
+
+        let a_move = &self.moves[self.position()];
+
+        let to = &mut self.stacks.0[a_move.to - 1].0;
+        to.truncate(to.len() - taken.len());
+
+        self.stacks.0[a_move.from - 1].0.extend(taken);
+
+        true
+    }
+
+    /// Goes forwards or backwards until `position` moves have been made, or as many as there are.
+    fn jump(&mut self, position: usize) -> Result<(), MoveError> {
+        while self.position() > position {
+            self.back();
+        }
+
+        while self.position() < position && self.step()? {}
+
+        Ok(())
+    }
+
+    /// The first move that puts a crate labelled `label` onto stack `stack`, counting both from
+    /// 1. Labels needn't be unique, so any crate with that label counts.
+    fn first_arrival(&mut self, label: &str, stack: usize) -> Result<Option<usize>, MoveError> {
+        let count = |stacks: &Stacks| {
+            stacks.0.get(stack.wrapping_sub(1)).map_or(0, |stack| {
+                stack.0.iter().filter(|other| *other == label).count()
+            })
+        };
+
+        self.jump(0)?;
+
+        loop {
+            let before = count(&self.stacks);
+
+            if !self.step()? {
+                return Ok(None);
+            }
+
+            if count(&self.stacks) > before {
+                return Ok(Some(self.position()));
+            }
+        }
+    }
+
+    /// The drawing before the first move and after every move, separated by blank lines. Leaves
+    /// the replay at the end.
+    fn frames(&mut self) -> Result<String, MoveError> {
+        self.jump(0)?;
+
+        let mut frames = format!("start\n{}\n", self.stacks);
+
+        while self.step()? {
+            let a_move = &self.moves[self.position() - 1];
+            frames += &format!("\n{}: {a_move}\n{}\n", self.position(), self.stacks);
+        }
+
+        Ok(frames)
+    }
+}
+
+/// Checks that every move can be made, in order, without moving any crates.
+fn dry_run(stacks: &Stacks, moves: &[Move]) -> Result<(), MoveError> {
+    let mut heights = stacks.heights();
+
     for a_move in moves {
-        stacks.move_crates_together(a_move);
+        let (from, to) = a_move.check(heights.len(), |i| heights[i])?;
+
+        heights[from] -= a_move.count;
+        heights[to] += a_move.count;
     }
 
-    stacks.0.iter().filter_map(|stack| stack.peak()).collect()
+    Ok(())
+}
+
+type Parsed = (Stacks, Vec<Move>);
+
+type Part1 = String;
+type Part2 = String;
+
+fn parse_stacks(input: Input<'_>) -> PResult<'_, Stacks> {
+    let footer = line(preceded(spaces, separated(StackNumber::parse, spaces)));
+
+    let (input, (rows, footer)) = pair(many(Crates::parse), footer).parse(input)?;
+
+    for (i, stack_number) in footer.iter().enumerate() {
+        if stack_number.number != i + 1 {
+            return Err(parse::Error {
+                position: stack_number.position,
+                expected: format!("stack number {}", i + 1),
+            });
+        }
+    }
+
+    let mut stacks = vec![Stack::new(); footer.len()];
+
+    for a_crate in rows.into_iter().rev().flat_map(|row| row.0) {
+        let stack = footer.iter().position(|stack_number| {
+            stack_number.columns.start < a_crate.columns.end
+                && a_crate.columns.start < stack_number.columns.end
+        });
+
+        let Some(stack) = stack else {
+            return Err(parse::Error {
+                position: a_crate.position,
+                expected: String::from("a crate above a stack number"),
+            });
+        };
+
+        stacks[stack].push(a_crate.label);
+    }
+
+    Ok((input, Stacks(stacks)))
+}
+
+fn parse_input(input: &str) -> Result<Parsed, parse::Error> {
+    parse::parse(
+        input,
+        pair(terminated(parse_stacks, blank_lines), many(Move::parse)),
+    )
+}
+
+fn part_1(parsed: Parsed) -> Result<Part1, MoveError> {
+    run(&CrateMover9000, parsed)
+}
+
+fn part_2(parsed: Parsed) -> Result<Part2, MoveError> {
+    run(&CrateMover9001, parsed)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{Crane, CrateMover9000, CrateMover9001, MoveError, Replay};
+    use advent_2022::parse::Position;
+
     const INPUT: &str = r#"
     [D]    
 [N] [C]    
@@ -234,17 +614,233 @@ move 1 from 1 to 2
 
     #[test]
     fn part_1() {
-        let parsed = super::parse_input(INPUT);
-        let part_1_ans = super::part_1(parsed);
+        let parsed = super::parse_input(INPUT).unwrap();
+        let part_1_ans = super::part_1(parsed).unwrap();
 
         assert_eq!(part_1_ans, String::from("CMZ"));
     }
 
     #[test]
     fn part_2() {
-        let parsed = super::parse_input(INPUT);
-        let part_2_ans = super::part_2(parsed);
+        let parsed = super::parse_input(INPUT).unwrap();
+        let part_2_ans = super::part_2(parsed).unwrap();
 
         assert_eq!(part_2_ans, String::from("MCD"));
     }
+
+    #[test]
+    fn ten_or_more_stacks() {
+        let input = r#"
+                                            [L]
+[A] [B] [C] [D] [E] [F] [G] [H] [I] [J] [K] [M]
+ 1   2   3   4   5   6   7   8   9  10  11  12
+
+move 1 from 12 to 1
+        "#;
+
+        let parsed = super::parse_input(input).unwrap();
+
+        assert_eq!(parsed.0 .0.len(), 12);
+        assert_eq!(parsed.0 .0[11].0, ["M", "L"]);
+        assert_eq!(super::part_1(parsed).unwrap(), "LBCDEFGHIJKM");
+    }
+
+    #[test]
+    fn wide_cells_and_ragged_lines() {
+        let input = "\n[AB]\n[CD]      [EF]\n 1    2    3\n\nmove 2 from 1 to 3\n";
+
+        let parsed = super::parse_input(input).unwrap();
+
+        assert_eq!(parsed.0 .0[0].0, ["CD", "AB"]);
+        assert!(parsed.0 .0[1].0.is_empty());
+        assert_eq!(parsed.0 .0[2].0, ["EF"]);
+        assert_eq!(super::part_2(parsed).unwrap(), "AB");
+    }
+
+    #[test]
+    fn crates_must_be_above_a_stack() {
+        let input = INPUT.replace("[Z] [M] [P]", "[Z] [M] [P] [Q]");
+
+        let err = super::parse_input(&input).unwrap_err();
+
+        assert_eq!(
+            err.position,
+            Position {
+                line: 4,
+                column: 13
+            }
+        );
+        assert_eq!(err.expected, "a crate above a stack number");
+    }
+
+    #[test]
+    fn stacks_are_numbered_in_order() {
+        let input = INPUT.replace(" 1   2   3 ", " 1   3   2 ");
+
+        let err = super::parse_input(&input).unwrap_err();
+
+        assert_eq!(err.position, Position { line: 5, column: 6 });
+        assert_eq!(err.expected, "stack number 2");
+    }
+
+    #[test]
+    fn display_matches_the_puzzle() {
+        let (stacks, _) = super::parse_input(INPUT).unwrap();
+
+        let drawing: Vec<&str> = INPUT.lines().skip(1).take(4).collect();
+
+        assert_eq!(stacks.to_string(), drawing.join("\n"));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let input = INPUT.replace("[N] [C]", "[N] [C1234]");
+        let (mut stacks, _) = super::parse_input(&input).unwrap();
+
+        for _ in 0..10 {
+            stacks.0.push(super::Stack(vec![String::from("X")]));
+        }
+
+        let (parsed, moves) = super::parse_input(&format!("{stacks}\n\n")).unwrap();
+
+        assert!(moves.is_empty());
+        assert_eq!(parsed, stacks);
+    }
+
+    #[test]
+    fn invalid_moves_are_errors() {
+        // replaces the third move, on line 9
+        let with_move = |a_move: &str| {
+            let input = INPUT.replace("move 2 from 2 to 1", a_move);
+            super::parse_input(&input).unwrap()
+        };
+
+        let (stacks, moves) = with_move("move 2 from 0 to 1");
+        assert_eq!(
+            super::dry_run(&stacks, &moves),
+            Err(MoveError::NoSuchStack {
+                line: 9,
+                stack: 0,
+                stacks: 3
+            })
+        );
+
+        // the first two moves leave stack 2 with two crates
+        let (stacks, moves) = with_move("move 3 from 2 to 1");
+        assert_eq!(
+            super::dry_run(&stacks, &moves),
+            Err(MoveError::NotEnoughCrates {
+                line: 9,
+                stack: 2,
+                height: 2,
+                count: 3
+            })
+        );
+
+        // a failed move leaves the stacks as they were
+        let (mut stacks, moves) = with_move("move 1 from 2 to 4");
+        let before = stacks.clone();
+
+        assert!(CrateMover9001.apply(&mut stacks, &moves[2]).is_err());
+        assert_eq!(stacks, before);
+        assert!(super::part_1((stacks, moves)).is_err());
+    }
+
+    #[test]
+    fn dry_run() {
+        let (stacks, moves) = super::parse_input(INPUT).unwrap();
+
+        assert_eq!(super::dry_run(&stacks, &moves), Ok(()));
+    }
+
+    #[test]
+    fn batched_cranes() {
+        let (stacks, moves) = super::parse_input(INPUT).unwrap();
+        let run = |name| {
+            super::run(
+                &*super::crane(name).unwrap(),
+                (stacks.clone(), moves.clone()),
+            )
+        };
+
+        // no move is bigger than 3 crates, so batches of 3 are the same as the 9001
+        assert_eq!(run("batch-1"), Ok(String::from("CMZ")));
+        assert_eq!(run("batch-3"), Ok(String::from("MCD")));
+
+        // move 3 from 1 to 3 becomes a batch of N, D and then Z on its own
+        assert_eq!(run("batch-2"), Ok(String::from("MCZ")));
+
+        assert!(super::crane("batch-0").is_none());
+        assert!(super::crane("9002").is_none());
+    }
+
+    #[test]
+    fn replay() {
+        let (stacks, moves) = super::parse_input(INPUT).unwrap();
+        let mut replay = Replay::new(&CrateMover9001, stacks.clone(), &moves);
+
+        assert!(!replay.back());
+
+        replay.jump(10).unwrap();
+        assert_eq!(replay.position(), 4);
+        assert_eq!(replay.stacks().tops(), "MCD");
+
+        replay.jump(1).unwrap();
+        let mut after_first_move = stacks.clone();
+        CrateMover9001
+            .apply(&mut after_first_move, &moves[0])
+            .unwrap();
+        assert_eq!(replay.stacks(), &after_first_move);
+
+        while replay.back() {}
+        assert_eq!(replay.stacks(), &stacks);
+    }
+
+    #[test]
+    fn first_arrival() {
+        let (stacks, moves) = super::parse_input(INPUT).unwrap();
+        let mut replay = Replay::new(&CrateMover9000, stacks, &moves);
+
+        // D goes onto stack 1 and then gets moved along to stack 3
+        assert_eq!(replay.first_arrival("D", 1), Ok(Some(1)));
+        assert_eq!(replay.first_arrival("D", 3), Ok(Some(2)));
+        assert_eq!(replay.first_arrival("P", 3), Ok(None));
+        assert_eq!(replay.first_arrival("D", 4), Ok(None));
+    }
+
+    #[test]
+    fn frames() {
+        let (stacks, moves) = super::parse_input(INPUT).unwrap();
+        let mut replay = Replay::new(&CrateMover9000, stacks, &moves);
+
+        let frames = replay.frames().unwrap();
+        let frames: Vec<&str> = frames.split("\n\n").collect();
+
+        assert_eq!(frames.len(), 5);
+        assert_eq!(
+            frames[0],
+            format!("start\n{}", INPUT[1..].split("\n\n").next().unwrap())
+        );
+        assert!(frames[4].starts_with("4: move 1 from 1 to 2\n"));
+
+        // every frame is a drawing that can be parsed again
+        for frame in frames {
+            let drawing = frame.split_once('\n').unwrap().1;
+            super::parse_input(&format!("{drawing}\n\n")).unwrap();
+        }
+    }
+
+    #[test]
+    fn moving_onto_the_same_stack() {
+        let (stacks, _) = super::parse_input(INPUT).unwrap();
+        let (_, moves) =
+            super::parse_input(&INPUT.replace("move 1 from 2 to 1", "move 2 from 2 to 2")).unwrap();
+
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            let mut after = stacks.clone();
+            crane.apply(&mut after, &moves[0]).unwrap();
+
+            assert_eq!(after, stacks);
+        }
+    }
 }
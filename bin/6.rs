@@ -1,8 +1,37 @@
-use std::fs::read_to_string;
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::{read_to_string, File},
+    io::{self, BufReader, Read},
+    ops::Range,
+};
+use tokio::io::{AsyncRead, AsyncReadExt};
 
 const PROBLEM: u8 = 6;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let window = std::env::args()
+        .skip_while(|arg| arg != "--window")
+        .nth(1)
+        .map(|size| size.parse::<usize>())
+        .transpose()?;
+
+    if window == Some(0) {
+        return Err("--window has to be at least 1".into());
+    }
+
+    // e.g. `generate-signal | cargo run --bin 6 -- --stream --window 14`
+    if std::env::args().any(|arg| arg == "--stream") {
+        let mut markers = AsyncMarkers::new(tokio::io::stdin(), window.unwrap_or(4));
+
+        while let Some(marker) = markers.next_marker().await? {
+            println!("{marker}");
+        }
+
+        return Ok(());
+    }
+
     let input = read_to_string(format!("inputs/{PROBLEM}.txt"))?;
 
     let parsed = parse_input(&input);
@@ -14,66 +43,302 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("part-1: {:?}", part_1);
     println!("part-2: {:?}", part_2);
 
+    if let Some(size) = window {
+        let file = File::open(format!("inputs/{PROBLEM}.txt"))?;
+        let markers = Markers::new(file, size).collect::<io::Result<Vec<_>>>()?;
+
+        println!("window-{size}: {:?}", markers.first());
+        println!("window-{size} markers: {}", markers.len());
+    }
+
+    if std::env::args().any(|arg| arg == "--frames") {
+        for frame in decode(&parsed) {
+            println!("{frame}");
+        }
+    }
+
     Ok(())
 }
 
+/// The last `size` bytes of a signal, which knows whether they're all different in O(1).
 #[derive(Debug)]
-struct Buffer<const N: usize>([u8; N], usize);
+struct Window {
+    size: usize,
+    bytes: VecDeque<u8>,
+    counts: [usize; 256],
+    /// How many different bytes are in the window more than once.
+    duplicates: usize,
+}
 
-impl<const N: usize> Buffer<N> {
-    fn new(inner: [u8; N]) -> Self {
-        Self(inner, 0)
+impl Window {
+    fn new(size: usize) -> Self {
+        assert!(size > 0, "a window has to hold at least one byte");
+
+        Self {
+            size,
+            bytes: VecDeque::with_capacity(size),
+            counts: [0; 256],
+            duplicates: 0,
+        }
     }
 
+    /// Adds `val` to the window, pushing out the oldest byte once it's full.
     fn insert(&mut self, val: u8) {
-        let Self(mut inner, i) = self;
+        if self.bytes.len() == self.size {
+            let oldest = self.bytes.pop_front().unwrap() as usize;
 
-        inner[*i] = val;
+            self.counts[oldest] -= 1;
+            if self.counts[oldest] == 1 {
+                self.duplicates -= 1;
+            }
+        }
+
+        self.bytes.push_back(val);
+
+        self.counts[val as usize] += 1;
+        if self.counts[val as usize] == 2 {
+            self.duplicates += 1;
+        }
+    }
+
+    /// Empties the window, as if no bytes had been inserted.
+    fn clear(&mut self) {
+        for val in self.bytes.drain(..) {
+            self.counts[val as usize] -= 1;
+        }
 
-        *self = Self(inner, (*i + 1) % N);
+        self.duplicates = 0;
     }
 
     fn are_all_unique(&self) -> bool {
-        self.0
-            .into_iter()
-            .map(|val| {
-                self.0
-                    .into_iter()
-                    .filter(|other| val == *other)
-                    .take(2)
-                    .count()
-            })
-            .all(|val| val == 1)
+        self.bytes.len() == self.size && self.duplicates == 0
+    }
+}
+
+/// Finds markers a byte at a time, so signals can be as long as they like.
+#[derive(Debug)]
+struct Detector {
+    window: Window,
+    read: usize,
+}
+
+impl Detector {
+    fn new(size: usize) -> Self {
+        Self {
+            window: Window::new(size),
+            read: 0,
+        }
+    }
+
+    /// How many bytes have been read, if `val` ends a marker. Whitespace, like the line break at
+    /// the end of a signal, counts as a byte but can't be part of a marker.
+    fn feed(&mut self, val: u8) -> Option<usize> {
+        self.read += 1;
+
+        if val.is_ascii_whitespace() {
+            self.window.clear();
+            return None;
+        }
+
+        self.window.insert(val);
+
+        self.window.are_all_unique().then_some(self.read)
+    }
+}
+
+/// Every marker in the signal from `reader`, as the byte offset of its end.
+///
+/// Markers can overlap: every byte that ends a run of `size` different bytes is a marker.
+struct Markers<R> {
+    bytes: io::Bytes<BufReader<R>>,
+    detector: Detector,
+}
+
+impl<R: Read> Markers<R> {
+    fn new(reader: R, size: usize) -> Self {
+        Self {
+            bytes: BufReader::new(reader).bytes(),
+            detector: Detector::new(size),
+        }
+    }
+}
+
+impl<R: Read> Iterator for Markers<R> {
+    type Item = io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for val in self.bytes.by_ref() {
+            match val {
+                Ok(val) => {
+                    if let Some(marker) = self.detector.feed(val) {
+                        return Some(Ok(marker));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        None
+    }
+}
+
+/// [`Markers`] for an async reader.
+struct AsyncMarkers<R> {
+    reader: tokio::io::BufReader<R>,
+    detector: Detector,
+}
+
+impl<R: AsyncRead + Unpin> AsyncMarkers<R> {
+    fn new(reader: R, size: usize) -> Self {
+        Self {
+            reader: tokio::io::BufReader::new(reader),
+            detector: Detector::new(size),
+        }
+    }
+
+    /// The next marker, or `None` at the end of the signal.
+    async fn next_marker(&mut self) -> io::Result<Option<usize>> {
+        loop {
+            match self.reader.read_u8().await {
+                Ok(val) => {
+                    if let Some(marker) = self.detector.feed(val) {
+                        return Ok(Some(marker));
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(err) => return Err(err),
+            }
+        }
     }
 }
 
 type Parsed = Vec<u8>;
 
-type Part1 = usize;
-type Part2 = usize;
+type Part1 = Option<usize>;
+type Part2 = Option<usize>;
 
 fn parse_input(input: &str) -> Parsed {
     input.trim().as_bytes().to_vec()
 }
 
-fn solve<const N: usize>(parsed: &Parsed) -> usize {
-    let mut buffer = Buffer::<N>::new(parsed[0..N].try_into().unwrap());
-    let mut index = N;
+/// How many bytes have been read by the end of the first `size` bytes that are all different.
+fn find_marker(parsed: &[u8], size: usize) -> Option<usize> {
+    let mut detector = Detector::new(size);
 
-    if buffer.are_all_unique() {
-        return index;
+    parsed.iter().find_map(|val| detector.feed(*val))
+}
+
+fn solve<const N: usize>(parsed: &Parsed) -> Option<usize> {
+    find_marker(parsed, N)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Packet,
+    Message,
+}
+
+impl Kind {
+    fn marker_size(self) -> usize {
+        match self {
+            Kind::Packet => 4,
+            Kind::Message => 14,
+        }
     }
+}
 
-    for val in parsed.iter().skip(N) {
-        index += 1;
-        buffer.insert(*val);
+/// A marker and the data after it, up to the next marker of the same kind. Ranges are byte
+/// offsets into the signal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Frame {
+    kind: Kind,
+    marker: Range<usize>,
+    payload: Range<usize>,
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            Kind::Packet => "packet",
+            Kind::Message => "message",
+        };
+
+        write!(
+            f,
+            "{kind:<7} marker {:>5}..{:<5} payload {:>5}..{:<5} ({} bytes)",
+            self.marker.start,
+            self.marker.end,
+            self.payload.start,
+            self.payload.end,
+            self.payload.len()
+        )
+    }
+}
 
-        if buffer.are_all_unique() {
-            return index;
+/// The frames of one kind in a signal.
+///
+/// Markers of the same kind don't overlap: the search for the next one starts where the last one
+/// ended, so a frame's payload can be empty but never negative.
+struct Frames<'a> {
+    signal: &'a [u8],
+    kind: Kind,
+    /// Where the next frame's marker is.
+    next: Option<Range<usize>>,
+}
+
+impl<'a> Frames<'a> {
+    fn new(signal: &'a [u8], kind: Kind) -> Self {
+        Self {
+            signal,
+            kind,
+            next: next_marker(signal, kind, 0),
         }
     }
+}
+
+impl Iterator for Frames<'_> {
+    type Item = Frame;
 
-    index
+    fn next(&mut self) -> Option<Self::Item> {
+        let marker = self.next.take()?;
+
+        self.next = next_marker(self.signal, self.kind, marker.end);
+        let end = self
+            .next
+            .as_ref()
+            .map_or(self.signal.len(), |next| next.start);
+
+        Some(Frame {
+            kind: self.kind,
+            payload: marker.end..end,
+            marker,
+        })
+    }
+}
+
+/// Where the first marker of `kind` that starts at or after `from` is.
+fn next_marker(signal: &[u8], kind: Kind, from: usize) -> Option<Range<usize>> {
+    let size = kind.marker_size();
+    let mut detector = Detector::new(size);
+
+    signal[from..]
+        .iter()
+        .find_map(|val| detector.feed(*val))
+        .map(|read| from + read - size..from + read)
+}
+
+/// Every packet and message in the signal, in the order their markers start.
+fn decode(signal: &[u8]) -> impl Iterator<Item = Frame> + '_ {
+    let mut packets = Frames::new(signal, Kind::Packet).peekable();
+    let mut messages = Frames::new(signal, Kind::Message).peekable();
+
+    std::iter::from_fn(move || match (packets.peek(), messages.peek()) {
+        (Some(packet), Some(message)) if message.marker.start < packet.marker.start => {
+            messages.next()
+        }
+        (Some(_), _) => packets.next(),
+        (None, _) => messages.next(),
+    })
 }
 
 fn part_1(parsed: &Parsed) -> Part1 {
@@ -86,14 +351,14 @@ fn part_2(parsed: &Parsed) -> Part2 {
 
 #[cfg(test)]
 mod tests {
-    use super::{Part1, Part2};
+    use super::{AsyncMarkers, Frame, Frames, Kind, Markers, Part1, Part2};
 
     const INPUT: &str = r#"
 mjqjpqmgbljsphdztnvjfqwrcgsmlb
     "#;
 
-    const PART_1_TEST_ANS: Part1 = 7;
-    const PART_2_TEST_ANS: Part2 = 19;
+    const PART_1_TEST_ANS: Part1 = Some(7);
+    const PART_2_TEST_ANS: Part2 = Some(19);
 
     #[test]
     fn part_1() {
@@ -110,4 +375,151 @@ mjqjpqmgbljsphdztnvjfqwrcgsmlb
 
         assert_eq!(part_2_ans, PART_2_TEST_ANS);
     }
+
+    #[test]
+    fn examples() {
+        let examples = [
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+        ];
+
+        for (signal, part_1, part_2) in examples {
+            let parsed = super::parse_input(signal);
+
+            assert_eq!(super::solve::<4>(&parsed), Some(part_1));
+            assert_eq!(super::solve::<14>(&parsed), Some(part_2));
+        }
+    }
+
+    #[test]
+    fn any_window_size() {
+        let parsed = super::parse_input(INPUT);
+
+        // checks every window from scratch
+        let expected = |size: usize| {
+            parsed
+                .windows(size)
+                .position(|window| (0..size).all(|i| !window[i + 1..].contains(&window[i])))
+                .map(|i| i + size)
+        };
+
+        for size in 1..=parsed.len() + 1 {
+            assert_eq!(
+                super::find_marker(&parsed, size),
+                expected(size),
+                "size {size}"
+            );
+        }
+
+        assert_eq!(super::find_marker(&parsed, 1), Some(1));
+        assert_eq!(super::find_marker(b"aaaa", 2), None);
+    }
+
+    #[test]
+    fn short_signals_have_no_marker() {
+        assert_eq!(super::solve::<4>(&super::parse_input("abc")), None);
+        assert_eq!(super::solve::<14>(&super::parse_input("")), None);
+    }
+
+    #[test]
+    fn every_marker() {
+        let signal = "aabcdd\nefab\n";
+        let markers: Vec<usize> = Markers::new(signal.as_bytes(), 4)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        // `dd\ne` isn't a marker, but the line break still counts as a byte
+        assert_eq!(markers, [5, 11]);
+
+        // frames agree on where the markers are
+        let frames: Vec<usize> = Frames::new(signal.as_bytes(), Kind::Packet)
+            .map(|frame| frame.marker.end)
+            .collect();
+        assert_eq!(frames, markers);
+    }
+
+    #[tokio::test]
+    async fn async_markers() {
+        let parsed = super::parse_input(INPUT);
+        let mut markers = AsyncMarkers::new(&parsed[..], 14);
+        let mut found = vec![];
+
+        while let Some(marker) = markers.next_marker().await.unwrap() {
+            found.push(marker);
+        }
+
+        let expected: Vec<usize> = Markers::new(&parsed[..], 14)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(found.first().copied(), PART_2_TEST_ANS);
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn frames() {
+        let parsed = super::parse_input(INPUT);
+
+        let packets: Vec<Frame> = Frames::new(&parsed, Kind::Packet).collect();
+
+        assert_eq!(
+            packets[..2],
+            [
+                Frame {
+                    kind: Kind::Packet,
+                    marker: 3..7,
+                    payload: 7..7,
+                },
+                Frame {
+                    kind: Kind::Packet,
+                    marker: 7..11,
+                    payload: 11..11,
+                },
+            ]
+        );
+
+        // the frames cover everything after the first marker, without gaps
+        for pair in packets.windows(2) {
+            assert_eq!(pair[0].payload.end, pair[1].marker.start);
+        }
+        assert_eq!(packets.last().unwrap().payload.end, parsed.len());
+
+        // the first marker of each kind is where the puzzle's answers come from
+        let first = |kind| {
+            Frames::new(&parsed, kind)
+                .next()
+                .map(|frame| frame.marker.end)
+        };
+        assert_eq!(first(Kind::Packet), PART_1_TEST_ANS);
+        assert_eq!(first(Kind::Message), PART_2_TEST_ANS);
+    }
+
+    #[test]
+    fn decode() {
+        let parsed = super::parse_input(INPUT);
+
+        let frames: Vec<Frame> = super::decode(&parsed).collect();
+
+        assert!(frames
+            .windows(2)
+            .all(|pair| pair[0].marker.start <= pair[1].marker.start));
+        assert_eq!(
+            frames
+                .iter()
+                .filter(|frame| frame.kind == Kind::Message)
+                .count(),
+            1
+        );
+
+        // no four different bytes after the first marker, so the rest is all payload
+        let frames: Vec<Frame> = super::decode(b"abcdaaab").collect();
+
+        assert_eq!(frames.len(), 1);
+        assert_eq!(
+            frames[0].to_string(),
+            "packet  marker     0..4     payload     4..8     (4 bytes)"
+        );
+    }
 }
@@ -1,11 +1,44 @@
-use std::{collections::HashMap, fs::read_to_string, iter::once};
+use advent_2022::parse::{
+    self, alt, lines, many, map, pair, preceded, spaces, tag, take_while1, terminated, try_map,
+    unsigned, Input, PResult, Parser, Position,
+};
+use std::{collections::HashMap, fmt, fs::read_to_string};
 
 const PROBLEM: u8 = 7;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let input = read_to_string(format!("inputs/{PROBLEM}.txt"))?;
 
-    let parsed = parse_input(&input);
+    let parsed = parse_input(&input)?;
+
+    let arg = |name: &str| std::env::args().skip_while(|arg| arg != name).nth(1);
+
+    if let Some(path) = arg("--du") {
+        let node = parsed
+            .get(&path)
+            .ok_or(format!("no such file or directory: {path}"))?;
+        println!("{}\t{path}", node.size);
+
+        return Ok(());
+    }
+
+    if let Some(pattern) = arg("--glob") {
+        for (path, node) in parsed.glob(&pattern) {
+            println!("{}\t{path}", node.size);
+        }
+
+        return Ok(());
+    }
+
+    if let Some(size) = arg("--min-size") {
+        let size = size.parse::<usize>()?;
+
+        for (path, node) in parsed.find(|node| node.is_dir() && node.size >= size) {
+            println!("{}\t{path}", node.size);
+        }
+
+        return Ok(());
+    }
 
     let part_1 = part_1(&parsed);
     let part_2 = part_2(&parsed);
@@ -14,193 +47,631 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("part-1: {:?}", part_1);
     println!("part-2: {:?}", part_2);
 
+    if std::env::args().any(|arg| arg == "--explain") {
+        println!("small directories:");
+        for id in small_dirs(&parsed) {
+            println!("  {}\t{}", parsed.node(id).size, parsed.path(id));
+        }
+
+        match dir_to_delete(&parsed) {
+            Some(id) => println!(
+                "directory to delete:\n  {}\t{}",
+                parsed.node(id).size,
+                parsed.path(id)
+            ),
+            None => println!("nothing to delete"),
+        }
+    }
+
     Ok(())
 }
 
+/// An index into [`FileSystem::nodes`].
+type NodeId = usize;
+
+#[derive(Debug, PartialEq, Eq)]
+enum NodeKind {
+    Dir(HashMap<String, NodeId>),
+    File,
+}
+
 #[derive(Debug, PartialEq, Eq)]
-enum File {
-    Dir(HashMap<String, File>),
-    File(usize),
+struct Node {
+    name: String,
+    parent: Option<NodeId>,
+    kind: NodeKind,
+    /// For a directory, the total size of everything in it, once
+    /// [`FileSystem::add_up_sizes`] has run.
+    size: usize,
 }
 
-impl File {
-    fn new_file(size: usize) -> Self {
-        Self::File(size)
+impl Node {
+    fn is_dir(&self) -> bool {
+        matches!(self.kind, NodeKind::Dir(..))
     }
+}
 
-    fn new_dir() -> Self {
-        Self::Dir(Default::default())
-    }
+/// Every file and directory in one list, linked to their parents by index.
+///
+/// A node always comes after its parent, so directory sizes can be added up in a single pass from
+/// the end of the list, without walking the tree.
+#[derive(Debug, PartialEq, Eq)]
+struct FileSystem {
+    nodes: Vec<Node>,
+}
 
-    fn insert(&mut self, name: String, file: Self) {
-        if let Self::Dir(contents) = self {
-            contents.insert(name, file);
+impl FileSystem {
+    const ROOT: NodeId = 0;
+
+    fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: String::new(),
+                parent: None,
+                kind: NodeKind::Dir(HashMap::new()),
+                size: 0,
+            }],
         }
     }
 
-    fn remove(&mut self, name: &str) -> Option<(String, File)> {
-        match self {
-            File::Dir(map) => map.remove_entry(name),
-            _ => None,
+    fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        match &self.nodes[dir].kind {
+            NodeKind::Dir(children) => children.get(name).copied(),
+            NodeKind::File => None,
         }
     }
 
-    fn size(&self) -> usize {
-        match self {
-            File::File(size) => *size,
-            File::Dir(files) => files.values().map(|file| file.size()).sum(),
+    /// The full path of a node, like `/a/e`.
+    fn path(&self, id: NodeId) -> String {
+        let mut names = vec![];
+        let mut node = Some(id);
+
+        while let Some(id) = node.filter(|id| *id != Self::ROOT) {
+            names.push(self.nodes[id].name.as_str());
+            node = self.parent(id);
         }
+
+        names.reverse();
+        format!("/{}", names.join("/"))
     }
 
-    fn iter(&self) -> Box<dyn Iterator<Item = &Self> + '_> {
-        match self {
-            File::Dir(contents) => {
-                let children = contents
-                    .iter()
-                    .map(|(_, file)| file)
-                    .flat_map(|child| child.iter());
+    /// The file or directory `path` leads to from `from`. Paths starting with `/` start at the
+    /// root, and `..` from the root stays there, like it does in a shell.
+    fn lookup(&self, from: NodeId, path: &str) -> Result<NodeId, PathError> {
+        let start = if path.starts_with('/') {
+            Self::ROOT
+        } else {
+            from
+        };
+
+        path.split('/')
+            .filter(|segment| !segment.is_empty() && *segment != ".")
+            .try_fold(start, |node, segment| {
+                if !self.nodes[node].is_dir() {
+                    return Err(PathError::NotADir(self.path(node)));
+                }
 
-                Box::new(once(self).chain(children))
-            }
-            _ => Box::new(once(self)),
+                if segment == ".." {
+                    return Ok(self.parent(node).unwrap_or(node));
+                }
+
+                self.child(node, segment)
+                    .ok_or_else(|| PathError::NotFound(self.join(node, segment)))
+            })
+    }
+
+    /// Like [`FileSystem::lookup`], but `path` has to lead to a directory.
+    fn resolve(&self, from: NodeId, path: &str) -> Result<NodeId, PathError> {
+        let id = self.lookup(from, path)?;
+
+        if self.nodes[id].is_dir() {
+            Ok(id)
+        } else {
+            Err(PathError::NotADir(self.path(id)))
         }
     }
 
-    fn is_dir(&self) -> bool {
-        matches!(self, Self::Dir(..))
+    /// The node at an absolute `path`.
+    fn get(&self, path: &str) -> Option<&Node> {
+        self.lookup(Self::ROOT, path).ok().map(|id| self.node(id))
     }
-}
 
-type Parsed = File;
+    /// Every node with its path, parents before their children and siblings by name.
+    fn walk(&self) -> Walk<'_> {
+        Walk {
+            file_system: self,
+            stack: vec![(Self::ROOT, String::from("/"))],
+        }
+    }
 
-type Part1 = usize;
-type Part2 = usize;
+    fn find<'a>(
+        &'a self,
+        predicate: impl Fn(&Node) -> bool + 'a,
+    ) -> impl Iterator<Item = (String, &'a Node)> + 'a {
+        self.walk().filter(move |(_, node)| predicate(node))
+    }
 
-struct WorkingDirectory(Vec<(String, File)>);
+    /// Every node whose path matches `pattern`, where `?` is any one character and `*` any
+    /// number of them within a name, and `**` is any number of directories.
+    ///
+    /// Only directories that could still lead to a match are visited.
+    fn glob<'a>(&'a self, pattern: &'a str) -> Glob<'a> {
+        let pattern: Vec<_> = segments(pattern).collect();
+        let states = Glob::closure(&pattern, vec![0]);
+
+        Glob {
+            file_system: self,
+            pattern,
+            stack: vec![(Self::ROOT, String::from("/"), states)],
+        }
+    }
 
-impl WorkingDirectory {
-    fn new(name: String, file: File) -> Self {
-        Self(vec![(name, file)])
+    fn join(&self, dir: NodeId, name: &str) -> String {
+        match dir {
+            Self::ROOT => format!("/{name}"),
+            _ => format!("{}/{name}", self.path(dir)),
+        }
     }
 
-    fn back(&mut self) {
-        if self.0.len() == 1 {
-            return;
+    /// Adds a directory, or finds the one that's already there. `None` if there's a file by that
+    /// name.
+    fn add_dir(&mut self, parent: NodeId, name: String) -> Option<NodeId> {
+        match self.child(parent, &name) {
+            Some(id) => self.nodes[id].is_dir().then_some(id),
+            None => Some(self.add(parent, name, NodeKind::Dir(HashMap::new()), 0)),
         }
+    }
 
-        let (name, file) = self.0.pop().unwrap();
+    /// Adds a file, unless it's already there. `None` if there's a directory, or a file of a
+    /// different size, by that name.
+    fn add_file(&mut self, parent: NodeId, name: String, size: usize) -> Option<NodeId> {
+        if let Some(id) = self.child(parent, &name) {
+            let node = &self.nodes[id];
+            return (!node.is_dir() && node.size == size).then_some(id);
+        }
 
-        self.0
-            .last_mut()
-            .map(|(_, dir)| dir)
-            .unwrap()
-            .insert(name, file);
+        Some(self.add(parent, name, NodeKind::File, size))
     }
 
-    fn enter(&mut self, name: &str) {
-        let child = self.0.last_mut().unwrap().1.remove(name).unwrap();
-        self.0.push(child);
+    /// Sets the size of every directory to the total size of everything in it.
+    fn add_up_sizes(&mut self) {
+        for node in &mut self.nodes {
+            if node.is_dir() {
+                node.size = 0;
+            }
+        }
+
+        // by the time a node is reached, everything in it has already been added to it
+        for id in (0..self.nodes.len()).rev() {
+            if let Some(parent) = self.parent(id) {
+                self.nodes[parent].size += self.nodes[id].size;
+            }
+        }
     }
 
-    fn insert(&mut self, name: String, file: File) {
-        let last = self.0.last_mut().map(|file| &mut file.1).unwrap();
-        last.insert(name, file);
+    fn add(&mut self, parent: NodeId, name: String, kind: NodeKind, size: usize) -> NodeId {
+        let id = self.nodes.len();
+
+        match &mut self.nodes[parent].kind {
+            NodeKind::Dir(children) => children.insert(name.clone(), id),
+            NodeKind::File => panic!("can't add {name:?} to a file"),
+        };
+
+        self.nodes.push(Node {
+            name,
+            parent: Some(parent),
+            kind,
+            size,
+        });
+
+        id
     }
+}
 
-    fn into_file(mut self) -> File {
-        while self.0.len() > 1 {
-            self.back();
+struct Walk<'a> {
+    file_system: &'a FileSystem,
+    /// The nodes still to visit and their paths, the next one last.
+    stack: Vec<(NodeId, String)>,
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = (String, &'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, path) = self.stack.pop()?;
+        let node = self.file_system.node(id);
+
+        if let NodeKind::Dir(children) = &node.kind {
+            let mut children: Vec<_> = children.iter().collect();
+            children.sort_by_key(|(name, _)| std::cmp::Reverse(*name));
+
+            // only the root's path ends in a `/`
+            let parent = path.trim_end_matches('/');
+
+            self.stack.extend(
+                children
+                    .into_iter()
+                    .map(|(name, id)| (*id, format!("{parent}/{name}"))),
+            );
         }
 
-        self.0.pop().unwrap().1
+        Some((path, node))
     }
 }
 
-fn parse_input(input: &str) -> Parsed {
-    let mut lines = input.trim().lines();
-    lines.next(); // skip the first line, it's just creating the initial directory;
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
 
-    let file = File::new_dir();
+/// [`FileSystem::walk`], but matching paths against a glob as it goes.
+///
+/// The pattern is run like an NFA: each node carries the set of pattern segments that could
+/// match next, so a directory nothing more can match in is skipped along with everything in it.
+struct Glob<'a> {
+    file_system: &'a FileSystem,
+    pattern: Vec<&'a str>,
+    /// The nodes still to visit, their paths and the pattern segments they've got up to.
+    stack: Vec<(NodeId, String, Vec<usize>)>,
+}
 
-    let mut current_dir = WorkingDirectory::new(String::from("/"), file);
+impl Glob<'_> {
+    /// `states` along with everything reachable from them by matching `**` against nothing.
+    fn closure(pattern: &[&str], mut states: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
 
-    let mut next = lines.next();
+        while i < states.len() {
+            if pattern.get(states[i]) == Some(&"**") {
+                states.push(states[i] + 1);
+            }
 
-    while let Some(line) = next {
-        match &line[0..4] {
-            "$ cd" => {
-                let name = &line[5..];
+            i += 1;
+        }
 
-                match name {
-                    ".." => current_dir.back(),
-                    name => current_dir.enter(name),
-                };
+        states.sort_unstable();
+        states.dedup();
+        states
+    }
 
-                next = lines.next();
-            }
+    /// The states after matching `name` from `states`.
+    fn step(&self, states: &[usize], name: &str) -> Vec<usize> {
+        let name: Vec<_> = name.chars().collect();
+
+        let next = states
+            .iter()
+            .filter_map(|&i| match self.pattern.get(i) {
+                Some(&"**") => Some(i),
+                Some(segment) => {
+                    let segment: Vec<_> = segment.chars().collect();
+                    wildcard_matches(&segment, &name).then_some(i + 1)
+                }
+                None => None,
+            })
+            .collect();
+
+        Self::closure(&self.pattern, next)
+    }
+}
+
+impl<'a> Iterator for Glob<'a> {
+    type Item = (String, &'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (id, path, states) = self.stack.pop()?;
+            let node = self.file_system.node(id);
+
+            if let NodeKind::Dir(children) = &node.kind {
+                let mut children: Vec<_> = children.iter().collect();
+                children.sort_by_key(|(name, _)| std::cmp::Reverse(*name));
 
-            "$ ls" => {
-                next = loop {
-                    match lines.next() {
-                        Some(line) if !line.starts_with('$') => {
-                            let (size, name) = line.split_once(' ').unwrap();
-
-                            let child = if size == "dir" {
-                                File::new_dir()
-                            } else {
-                                File::new_file(size.parse().unwrap())
-                            };
-
-                            current_dir.insert(name.to_string(), child);
-                        }
-                        line => break line,
+                let parent = path.trim_end_matches('/');
+
+                for (name, id) in children {
+                    let states = self.step(&states, name);
+
+                    if !states.is_empty() {
+                        self.stack.push((*id, format!("{parent}/{name}"), states));
                     }
                 }
             }
-            _ => unreachable!(),
+
+            if states.contains(&self.pattern.len()) {
+                return Some((path, node));
+            }
         }
     }
+}
 
-    current_dir.into_file()
+/// Whether `name` matches `pattern`, in O(pattern × name).
+///
+/// A `*` only ever has to be retried from the most recent one: whatever an earlier `*` could
+/// match instead, the later one can match just as well.
+fn wildcard_matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // the last `*` and where in `name` it's currently matching up to
+    let mut star = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
 }
 
-fn part_1(parsed: &Parsed) -> Part1 {
-    const MAX_SIZE: usize = 100000;
+#[derive(Debug, PartialEq, Eq)]
+enum PathError {
+    NotFound(String),
+    NotADir(String),
+}
 
-    parsed
-        .iter()
-        .filter_map(|file| {
-            if file.is_dir() {
-                Some(file.size()).filter(|size| *size <= MAX_SIZE)
-            } else {
-                None
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::NotFound(path) => write!(f, "no directory {path} has been listed"),
+            PathError::NotADir(path) => write!(f, "{path} is a file, not a directory"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Error {
+    Parse(parse::Error),
+    /// A `cd` to somewhere that doesn't exist.
+    Path {
+        line: usize,
+        err: PathError,
+    },
+    /// An `ls` shows an entry differently from how an earlier one did.
+    Conflict {
+        line: usize,
+        path: String,
+    },
+    /// Output after a command that doesn't print anything.
+    UnexpectedOutput {
+        line: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(err) => write!(f, "{err}"),
+            Error::Path { line, err } => write!(f, "line {line}: {err}"),
+            Error::Conflict { line, path } => {
+                write!(
+                    f,
+                    "line {line}: {path} doesn't match what was listed before"
+                )
             }
-        })
-        .sum()
+            Error::UnexpectedOutput { line } => {
+                write!(
+                    f,
+                    "line {line}: output from a command that doesn't print anything"
+                )
+            }
+        }
+    }
 }
 
-fn part_2(parsed: &Parsed) -> Part2 {
-    const TOTAL_SPACE: usize = 70000000;
-    const UPDATE_SIZE: usize = 30000000;
+impl std::error::Error for Error {}
+
+impl From<parse::Error> for Error {
+    fn from(err: parse::Error) -> Self {
+        Self::Parse(err)
+    }
+}
+
+type Parsed = FileSystem;
+
+type Part1 = usize;
+type Part2 = Option<usize>;
+
+enum Line {
+    Cd(String),
+    Ls,
+    /// A command other than `cd` or `ls`.
+    Unknown,
+    /// Anything that isn't a command, and where it starts.
+    Output(Position, String),
+}
+
+impl Line {
+    /// A line of the transcript, along with its line number.
+    fn parse(input: Input<'_>) -> PResult<'_, (usize, Self)> {
+        let name = || take_while1("a name", |c| !c.is_whitespace());
+
+        let command = try_map(
+            pair(name(), many(preceded(spaces, name()))),
+            "cd with one path",
+            |(command, mut args)| match (command.as_str(), args.len()) {
+                ("cd", 1) => args.pop().map(Self::Cd),
+                ("cd", _) => None,
+                ("ls", 0) => Some(Self::Ls),
+                _ => Some(Self::Unknown),
+            },
+        );
+
+        let position = input.position();
+        let output = map(
+            take_while1("output", |c| c != '\n' && c != '\r'),
+            move |text| Self::Output(position, text),
+        );
+
+        let line = if input.rest().starts_with('$') {
+            preceded(tag("$ "), command).parse(input)
+        } else {
+            output.parse(input)
+        };
+
+        line.map(|(input, line)| (input, (position.line, line)))
+    }
+}
+
+/// A line of `ls` output.
+enum Entry {
+    Dir(String),
+    File(usize, String),
+}
+
+impl Entry {
+    fn parse(input: Input<'_>) -> PResult<'_, Self> {
+        let name = || take_while1("a name", |c| !c.is_whitespace());
+
+        alt(
+            map(preceded(tag("dir "), name()), Self::Dir),
+            map(
+                pair(terminated(unsigned(), tag(" ")), name()),
+                |(size, name)| Self::File(size, name),
+            ),
+        )
+        .parse(input)
+    }
+}
+
+/// What the lines after the last command are.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Output {
+    /// There shouldn't be any, like after a `cd`.
+    Nothing,
+    Listing,
+    /// The output of a command that isn't `cd` or `ls`, which is ignored.
+    Skipped,
+}
+
+fn parse_input(input: &str) -> Result<Parsed, Error> {
+    let lines = parse::parse(input, lines(Line::parse))?;
+
+    let mut file_system = FileSystem::new();
+    let mut current_dir = FileSystem::ROOT;
+    let mut output = Output::Nothing;
+
+    for (line, entry) in lines {
+        let entry = match entry {
+            Line::Cd(path) => {
+                current_dir = file_system
+                    .resolve(current_dir, &path)
+                    .map_err(|err| Error::Path { line, err })?;
+                output = Output::Nothing;
 
-    let space_needed = UPDATE_SIZE - (TOTAL_SPACE - parsed.size());
+                continue;
+            }
+            Line::Ls => {
+                output = Output::Listing;
+                continue;
+            }
+            Line::Unknown => {
+                output = Output::Skipped;
+                continue;
+            }
+            Line::Output(..) if output == Output::Skipped => continue,
+            Line::Output(..) if output == Output::Nothing => {
+                return Err(Error::UnexpectedOutput { line })
+            }
+            Line::Output(position, text) => {
+                // parsed on its own, so the error has to be moved back to where the line is
+                parse::parse(&text, Entry::parse).map_err(|err| {
+                    Error::Parse(parse::Error {
+                        position: Position {
+                            line,
+                            column: position.column + err.position.column - 1,
+                        },
+                        ..err
+                    })
+                })?
+            }
+        };
+
+        let added = match entry {
+            Entry::Dir(name) => file_system.add_dir(current_dir, name.clone()).ok_or(name),
+            Entry::File(size, name) => file_system
+                .add_file(current_dir, name.clone(), size)
+                .ok_or(name),
+        };
+
+        added.map_err(|name| Error::Conflict {
+            line,
+            path: file_system.join(current_dir, &name),
+        })?;
+    }
 
+    file_system.add_up_sizes();
+
+    Ok(file_system)
+}
+
+const MAX_SIZE: usize = 100000;
+const TOTAL_SPACE: usize = 70000000;
+const UPDATE_SIZE: usize = 30000000;
+
+/// The directories part 1 adds up.
+fn small_dirs(parsed: &Parsed) -> Vec<NodeId> {
+    dirs(parsed)
+        .filter(|(_, dir)| dir.size <= MAX_SIZE)
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// The smallest directory that frees up enough space for the update, or `None` if there's
+/// already enough free space.
+fn dir_to_delete(parsed: &Parsed) -> Option<NodeId> {
+    let used = parsed.node(FileSystem::ROOT).size;
+    let free = TOTAL_SPACE.saturating_sub(used);
+    let space_needed = UPDATE_SIZE.checked_sub(free).filter(|needed| *needed > 0)?;
+
+    dirs(parsed)
+        .filter(|(_, dir)| dir.size >= space_needed)
+        .min_by_key(|(_, dir)| dir.size)
+        .map(|(id, _)| id)
+}
+
+/// Every directory, straight from the list so no paths have to be built.
+fn dirs(parsed: &Parsed) -> impl Iterator<Item = (NodeId, &Node)> {
     parsed
+        .nodes
         .iter()
-        .filter_map(|file| {
-            if file.is_dir() {
-                Some(file.size()).filter(|size| *size >= space_needed)
-            } else {
-                None
-            }
-        })
-        .min()
-        .unwrap()
+        .enumerate()
+        .filter(|(_, node)| node.is_dir())
+}
+
+fn part_1(parsed: &Parsed) -> Part1 {
+    small_dirs(parsed)
+        .into_iter()
+        .map(|id| parsed.node(id).size)
+        .sum()
+}
+
+fn part_2(parsed: &Parsed) -> Part2 {
+    dir_to_delete(parsed).map(|id| parsed.node(id).size)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Parsed, Part1, Part2};
+    use super::{Error, Parsed, Part1, Part2, PathError, Position};
 
     const INPUT: &str = r#"
 $ cd /
@@ -229,36 +700,132 @@ $ ls
     "#;
 
     const PART_1_TEST_ANS: Part1 = 95437;
-    const PART_2_TEST_ANS: Part2 = 24933642;
+    const PART_2_TEST_ANS: Part2 = Some(24933642);
 
     fn parsed_input() -> Parsed {
-        let mut d = Parsed::new_dir();
-        d.insert("j".into(), Parsed::new_file(4060174));
-        d.insert("d.log".into(), Parsed::new_file(8033020));
-        d.insert("d.ext".into(), Parsed::new_file(5626152));
-        d.insert("k".into(), Parsed::new_file(7214296));
-
-        let mut e = Parsed::new_dir();
-        e.insert("i".into(), Parsed::new_file(584));
-        let mut a = Parsed::new_dir();
-        a.insert("e".into(), e);
-        a.insert("f".into(), Parsed::new_file(29116));
-        a.insert("g".into(), Parsed::new_file(2557));
-        a.insert("h.lst".into(), Parsed::new_file(62596));
-
-        let mut parsed = Parsed::new_dir();
-
-        parsed.insert("a".into(), a);
-        parsed.insert("b.txt".into(), Parsed::new_file(14848514));
-        parsed.insert("c.dat".into(), Parsed::new_file(8504156));
-        parsed.insert("d".into(), d);
+        let mut parsed = Parsed::new();
+        let root = Parsed::ROOT;
+
+        let a = parsed.add_dir(root, "a".into()).unwrap();
+        parsed.add_file(root, "b.txt".into(), 14848514).unwrap();
+        parsed.add_file(root, "c.dat".into(), 8504156).unwrap();
+        let d = parsed.add_dir(root, "d".into()).unwrap();
+
+        let e = parsed.add_dir(a, "e".into()).unwrap();
+        parsed.add_file(a, "f".into(), 29116).unwrap();
+        parsed.add_file(a, "g".into(), 2557).unwrap();
+        parsed.add_file(a, "h.lst".into(), 62596).unwrap();
+
+        parsed.add_file(e, "i".into(), 584).unwrap();
+
+        parsed.add_file(d, "j".into(), 4060174).unwrap();
+        parsed.add_file(d, "d.log".into(), 8033020).unwrap();
+        parsed.add_file(d, "d.ext".into(), 5626152).unwrap();
+        parsed.add_file(d, "k".into(), 7214296).unwrap();
+
+        parsed.add_up_sizes();
 
         parsed
     }
 
     #[test]
     fn parse() {
-        assert_eq!(super::parse_input(INPUT), parsed_input());
+        assert_eq!(super::parse_input(INPUT), Ok(parsed_input()));
+    }
+
+    #[test]
+    fn paths() {
+        let parsed = parsed_input();
+
+        let e = parsed.resolve(Parsed::ROOT, "/a/e").unwrap();
+        assert_eq!(parsed.path(e), "/a/e");
+        assert_eq!(
+            parsed.resolve(e, "../../d/./"),
+            parsed.resolve(Parsed::ROOT, "d")
+        );
+        assert_eq!(parsed.resolve(e, "/"), Ok(Parsed::ROOT));
+        assert_eq!(parsed.resolve(Parsed::ROOT, ".."), Ok(Parsed::ROOT));
+
+        assert_eq!(
+            parsed.resolve(e, "../x"),
+            Err(PathError::NotFound(String::from("/a/x")))
+        );
+        assert_eq!(
+            parsed.resolve(e, "/b.txt"),
+            Err(PathError::NotADir(String::from("/b.txt")))
+        );
+    }
+
+    #[test]
+    fn flexible_transcripts() {
+        // no `cd /` to start with, absolute and multi-segment paths, and listing `/` again
+        let input = INPUT
+            .replacen("$ cd /\n", "", 1)
+            .replace(
+                "$ cd ..\n$ cd ..\n$ cd d",
+                "$ cd /\n$ ls\ndir a\n8504156 c.dat\n$ cd a/../d",
+            )
+            .replace("$ cd e", "$ cd /a/e");
+
+        assert_eq!(super::parse_input(&input), Ok(parsed_input()));
+    }
+
+    #[test]
+    fn inconsistent_transcripts_are_errors() {
+        let err = |from: &str, to: &str| super::parse_input(&INPUT.replace(from, to)).unwrap_err();
+
+        assert_eq!(
+            err("$ cd e", "$ cd x"),
+            Error::Path {
+                line: 14,
+                err: PathError::NotFound(String::from("/a/x"))
+            }
+        );
+        assert_eq!(
+            err("$ cd d\n", "$ ls\n584 d\n"),
+            Error::Conflict {
+                line: 20,
+                path: String::from("/d")
+            }
+        );
+        assert_eq!(
+            err("$ cd d\n$ ls\n", "$ cd d\n"),
+            Error::UnexpectedOutput { line: 20 }
+        );
+
+        let Error::Parse(entry) = err("584 i", "584i") else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(
+            entry.position,
+            Position {
+                line: 16,
+                column: 4
+            }
+        );
+        assert_eq!(entry.expected, r#"" ""#);
+
+        let Error::Parse(cd) = err("$ cd e", "$ cd e f") else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(
+            cd.position,
+            Position {
+                line: 14,
+                column: 3
+            }
+        );
+        assert_eq!(cd.expected, "cd with one path");
+    }
+
+    #[test]
+    fn unknown_commands_are_skipped() {
+        let input = INPUT.replace(
+            "$ cd d\n",
+            "$ pwd\n/\n$ echo dir x\ndir x\n$ ls -l d\n-rw-r--r-- 1 k\n$ cd d\n",
+        );
+
+        assert_eq!(super::parse_input(&input), Ok(parsed_input()));
     }
 
     #[test]
@@ -274,4 +841,123 @@ $ ls
 
         assert_eq!(part_2_ans, PART_2_TEST_ANS);
     }
+
+    #[test]
+    fn sizes_add_up() {
+        let mut parsed = parsed_input();
+
+        let e = parsed
+            .child(parsed.child(Parsed::ROOT, "a").unwrap(), "e")
+            .unwrap();
+
+        assert_eq!(parsed.node(e).size, 584);
+        assert_eq!(parsed.node(Parsed::ROOT).size, 48381165);
+
+        let dir_sizes: Vec<_> = parsed
+            .find(|node| node.is_dir())
+            .map(|(path, node)| (path, node.size))
+            .collect();
+        assert_eq!(
+            dir_sizes,
+            [
+                (String::from("/"), 48381165),
+                (String::from("/a"), 94853),
+                (String::from("/a/e"), 584),
+                (String::from("/d"), 24933642)
+            ]
+        );
+
+        // adding up again doesn't count anything twice
+        parsed.add_up_sizes();
+        assert_eq!(parsed, parsed_input());
+    }
+
+    #[test]
+    fn walk() {
+        let paths: Vec<_> = parsed_input().walk().map(|(path, _)| path).collect();
+
+        assert_eq!(
+            paths,
+            [
+                "/", "/a", "/a/e", "/a/e/i", "/a/f", "/a/g", "/a/h.lst", "/b.txt", "/c.dat", "/d",
+                "/d/d.ext", "/d/d.log", "/d/j", "/d/k"
+            ]
+        );
+    }
+
+    #[test]
+    fn get() {
+        let parsed = parsed_input();
+
+        assert_eq!(parsed.get("/a/e/i").map(|node| node.size), Some(584));
+        assert_eq!(parsed.get("a/e/../f").map(|node| node.size), Some(29116));
+        assert_eq!(parsed.get("/").map(|node| node.size), Some(48381165));
+        assert_eq!(parsed.get("/a/x"), None);
+        assert_eq!(parsed.get("/b.txt/x"), None);
+    }
+
+    #[test]
+    fn glob() {
+        let parsed = parsed_input();
+        let glob = |pattern| -> Vec<_> { parsed.glob(pattern).map(|(path, _)| path).collect() };
+
+        assert_eq!(glob("/*.???"), ["/b.txt", "/c.dat"]);
+        assert_eq!(glob("/d/d.*"), ["/d/d.ext", "/d/d.log"]);
+        assert_eq!(
+            glob("**/?"),
+            ["/a", "/a/e", "/a/e/i", "/a/f", "/a/g", "/d", "/d/j", "/d/k"]
+        );
+        assert_eq!(glob("/a/**/i"), ["/a/e/i"]);
+        assert_eq!(glob("/"), ["/"]);
+        assert!(glob("/x*").is_empty());
+    }
+
+    #[test]
+    fn wildcards() {
+        let matches = |pattern: &str, name: &str| {
+            let pattern: Vec<_> = pattern.chars().collect();
+            let name: Vec<_> = name.chars().collect();
+            super::wildcard_matches(&pattern, &name)
+        };
+
+        assert!(matches("*", ""));
+        assert!(matches("a*b?d", "aXXbcd"));
+        assert!(matches("*.*", "h.lst"));
+        assert!(matches("**a", "ba"));
+        assert!(!matches("a*", "ba"));
+        assert!(!matches("?", ""));
+        assert!(!matches("*b", "abc"));
+
+        // would take exponential time if every `*` were retried
+        let name = "a".repeat(36);
+        assert!(!matches("*a*a*a*a*a*a*b", &name));
+        assert!(matches("*a*a*a*a*a*a*", &name));
+    }
+
+    #[test]
+    fn selected_dirs() {
+        let parsed = parsed_input();
+
+        let small: Vec<_> = super::small_dirs(&parsed)
+            .into_iter()
+            .map(|id| parsed.path(id))
+            .collect();
+        assert_eq!(small, ["/a", "/a/e"]);
+        assert_eq!(
+            super::dir_to_delete(&parsed).map(|id| parsed.path(id)),
+            Some(String::from("/d"))
+        );
+    }
+
+    #[test]
+    fn enough_free_space() {
+        let mut parsed = Parsed::new();
+        parsed
+            .add_file(Parsed::ROOT, "a".into(), 40_000_000)
+            .unwrap();
+        parsed.add_up_sizes();
+
+        assert_eq!(super::dir_to_delete(&parsed), None);
+        assert_eq!(super::part_2(&parsed), None);
+    }
 }
@@ -0,0 +1,275 @@
+use std::{
+    fmt,
+    iter::FusedIterator,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign},
+};
+
+/// A set of small numbers, stored as `N` words of 64 bits each.
+///
+/// It can hold any value below `64 * N`, and inserting anything larger panics.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitSet<const N: usize>([u64; N]);
+
+/// Enough for the letters of the alphabet in both cases.
+pub type BitSet64 = BitSet<1>;
+
+/// Enough for every byte.
+pub type BitSet256 = BitSet<4>;
+
+impl<const N: usize> BitSet<N> {
+    pub const CAPACITY: usize = 64 * N;
+
+    pub const fn new() -> Self {
+        Self([0; N])
+    }
+
+    /// Returns whether the value was newly inserted.
+    pub fn insert(&mut self, val: usize) -> bool {
+        assert!(val < Self::CAPACITY, "{val} doesn't fit in a BitSet<{N}>");
+
+        let (word, bit) = (val / 64, 1 << (val % 64));
+        let is_new = self.0[word] & bit == 0;
+        self.0[word] |= bit;
+
+        is_new
+    }
+
+    /// Returns whether the value was present.
+    pub fn remove(&mut self, val: usize) -> bool {
+        if val >= Self::CAPACITY {
+            return false;
+        }
+
+        let (word, bit) = (val / 64, 1 << (val % 64));
+        let was_present = self.0[word] & bit != 0;
+        self.0[word] &= !bit;
+
+        was_present
+    }
+
+    pub fn contains(&self, val: usize) -> bool {
+        val < Self::CAPACITY && self.0[val / 64] & (1 << (val % 64)) != 0
+    }
+
+    /// The number of values in the set, i.e. its popcount.
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|word| *word == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = [0; N];
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.0.iter().zip(other.0).all(|(a, b)| a & !b == 0)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.0.iter().zip(other.0).all(|(a, b)| a & b == 0)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        *self | *other
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        *self & *other
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        *self - *other
+    }
+
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        *self ^ *other
+    }
+
+    /// The values in the set, smallest first.
+    pub fn iter(&self) -> Iter<N> {
+        Iter {
+            words: self.0,
+            word: 0,
+        }
+    }
+}
+
+impl<const N: usize> Default for BitSet<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Debug for BitSet<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<const N: usize> FromIterator<usize> for BitSet<N> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<const N: usize> Extend<usize> for BitSet<N> {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|val| {
+            self.insert(val);
+        });
+    }
+}
+
+impl<const N: usize> IntoIterator for BitSet<N> {
+    type Item = usize;
+    type IntoIter = Iter<N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<const N: usize> IntoIterator for &BitSet<N> {
+    type Item = usize;
+    type IntoIter = Iter<N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct Iter<const N: usize> {
+    words: [u64; N],
+    word: usize,
+}
+
+impl<const N: usize> Iterator for Iter<N> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.word < N {
+            let bits = &mut self.words[self.word];
+
+            if *bits != 0 {
+                let bit = bits.trailing_zeros() as usize;
+                // clear the lowest set bit
+                *bits &= *bits - 1;
+
+                return Some(self.word * 64 + bit);
+            }
+
+            self.word += 1;
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.words[self.word.min(N)..]
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum();
+
+        (len, Some(len))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for Iter<N> {}
+
+impl<const N: usize> FusedIterator for Iter<N> {}
+
+macro_rules! impl_op {
+    ($op:ident, $fn:ident, $op_assign:ident, $fn_assign:ident, |$a:ident, $b:ident| $body:expr) => {
+        impl<const N: usize> $op for BitSet<N> {
+            type Output = Self;
+
+            fn $fn(mut self, other: Self) -> Self {
+                self.$fn_assign(other);
+                self
+            }
+        }
+
+        impl<const N: usize> $op_assign for BitSet<N> {
+            fn $fn_assign(&mut self, other: Self) {
+                self.0
+                    .iter_mut()
+                    .zip(other.0)
+                    .for_each(|($a, $b)| *$a = $body);
+            }
+        }
+    };
+}
+
+impl_op!(BitOr, bitor, BitOrAssign, bitor_assign, |a, b| *a | b);
+impl_op!(BitAnd, bitand, BitAndAssign, bitand_assign, |a, b| *a & b);
+impl_op!(BitXor, bitxor, BitXorAssign, bitxor_assign, |a, b| *a ^ b);
+impl_op!(Sub, sub, SubAssign, sub_assign, |a, b| *a & !b);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_and_remove() {
+        let mut set = BitSet256::new();
+
+        assert!(set.insert(3));
+        assert!(set.insert(200));
+        assert!(!set.insert(3));
+
+        assert!(set.contains(3));
+        assert!(set.contains(200));
+        assert!(!set.contains(4));
+        assert!(!set.contains(1000));
+        assert_eq!(set.len(), 2);
+
+        assert!(set.remove(3));
+        assert!(!set.remove(3));
+        assert_eq!(set.len(), 1);
+
+        set.clear();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_range() {
+        BitSet64::new().insert(64);
+    }
+
+    #[test]
+    fn set_algebra() {
+        let a: BitSet<2> = [1, 2, 3, 100].into_iter().collect();
+        let b: BitSet<2> = [3, 4, 100, 127].into_iter().collect();
+
+        assert_eq!(
+            a.union(&b).iter().collect::<Vec<_>>(),
+            [1, 2, 3, 4, 100, 127]
+        );
+        assert_eq!(a.intersection(&b).iter().collect::<Vec<_>>(), [3, 100]);
+        assert_eq!(a.difference(&b).iter().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(
+            a.symmetric_difference(&b).iter().collect::<Vec<_>>(),
+            [1, 2, 4, 127]
+        );
+
+        assert!((a & b).is_subset(&a));
+        assert!(!a.is_subset(&b));
+        assert!((a - b).is_disjoint(&b));
+    }
+
+    #[test]
+    fn iter() {
+        let set: BitSet256 = [255, 0, 64, 63].into_iter().collect();
+        let iter = set.iter();
+
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.collect::<Vec<_>>(), [0, 63, 64, 255]);
+        assert_eq!(format!("{set:?}"), "{0, 63, 64, 255}");
+    }
+}
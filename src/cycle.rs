@@ -0,0 +1,344 @@
+use std::{collections::HashMap, fmt, hash::Hash};
+
+/// Where a sequence of states starts repeating and how long each repetition is.
+///
+/// All of the detectors below loop forever if the sequence never repeats, so they should only be
+/// used on simulations with a finite state space.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    /// The number of steps taken before the first state that is part of the cycle.
+    pub start: usize,
+    /// The number of steps it takes to get back to the same state once inside the cycle.
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The smallest number of steps that ends in the same state as taking `steps` steps.
+    pub fn reduce(&self, steps: usize) -> usize {
+        if steps < self.start {
+            steps
+        } else {
+            self.start + (steps - self.start) % self.length
+        }
+    }
+
+    /// Sums the accumulator produced by each `step` over `steps` steps from `initial`.
+    ///
+    /// Only the steps before the cycle and a single lap of the cycle are simulated, so `steps`
+    /// can be arbitrarily large, as long as the total fits in `A`.
+    pub fn extrapolate<S, A, F>(&self, initial: S, steps: usize, mut step: F) -> Result<A, Overflow>
+    where
+        A: Accumulator,
+        F: FnMut(&S) -> (S, A),
+    {
+        let mut state = initial;
+        let mut total = A::default();
+
+        for _ in 0..steps.min(self.start) {
+            let (next, acc) = step(&state);
+            total.add(&acc)?;
+            state = next;
+        }
+
+        if steps <= self.start {
+            return Ok(total);
+        }
+
+        let laps = (steps - self.start) / self.length;
+        let rest = (steps - self.start) % self.length;
+
+        let mut lap = A::default();
+        let mut partial = A::default();
+
+        // without a full lap to take, adding one up could overflow when the total wouldn't
+        let lap_steps = if laps > 0 { self.length } else { rest };
+
+        for i in 0..lap_steps {
+            let (next, acc) = step(&state);
+
+            if laps > 0 {
+                lap.add(&acc)?;
+            }
+
+            if i < rest {
+                partial.add(&acc)?;
+            }
+
+            state = next;
+        }
+
+        if laps > 0 {
+            lap.scale(laps)?;
+            total.add(&lap)?;
+        }
+
+        total.add(&partial)?;
+
+        Ok(total)
+    }
+}
+
+/// An [`Accumulator`] got too big for its type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the total is too large to represent")
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+/// Something that can be summed up per step and multiplied by a number of laps.
+///
+/// `Default` is the value before any step has been taken.
+pub trait Accumulator: Default {
+    fn add(&mut self, other: &Self) -> Result<(), Overflow>;
+    fn scale(&mut self, factor: usize) -> Result<(), Overflow>;
+}
+
+macro_rules! impl_accumulator {
+    ($($t:ty),*) => {
+        $(
+            impl Accumulator for $t {
+                fn add(&mut self, other: &Self) -> Result<(), Overflow> {
+                    *self = self.checked_add(*other).ok_or(Overflow)?;
+                    Ok(())
+                }
+
+                fn scale(&mut self, factor: usize) -> Result<(), Overflow> {
+                    let factor = <$t>::try_from(factor).map_err(|_| Overflow)?;
+                    *self = self.checked_mul(factor).ok_or(Overflow)?;
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_accumulator!(u8, usize, u32, u64, u128, isize, i32, i64, i128);
+
+impl<T: Accumulator + Clone> Accumulator for Vec<T> {
+    fn add(&mut self, other: &Self) -> Result<(), Overflow> {
+        if self.len() < other.len() {
+            self.resize_with(other.len(), T::default);
+        }
+
+        self.iter_mut()
+            .zip(other)
+            .try_for_each(|(val, other)| val.add(other))
+    }
+
+    fn scale(&mut self, factor: usize) -> Result<(), Overflow> {
+        self.iter_mut().try_for_each(|val| val.scale(factor))
+    }
+}
+
+/// Floyd's tortoise and hare: constant memory, but steps through the sequence about three times.
+pub fn floyd<S, F>(initial: &S, step: F) -> Cycle
+where
+    S: Clone + PartialEq,
+    F: Fn(&S) -> S,
+{
+    let mut tortoise = step(initial);
+    let mut hare = step(&step(initial));
+
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+
+    let mut start = 0;
+    tortoise = initial.clone();
+
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Brent's algorithm: constant memory and fewer steps than [`floyd`].
+pub fn brent<S, F>(initial: &S, step: F) -> Cycle
+where
+    S: Clone + PartialEq,
+    F: Fn(&S) -> S,
+{
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+
+        hare = step(&hare);
+        length += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+
+    let mut start = 0;
+
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Remembers the `key` of every state it has seen, so each step is only taken once.
+///
+/// Useful when states are expensive to step but cheap to summarize, or when only part of the
+/// state determines what happens next.
+pub fn hashed<S, K, F, G>(initial: S, mut step: F, mut key: G) -> Cycle
+where
+    K: Hash + Eq,
+    F: FnMut(&S) -> S,
+    G: FnMut(&S) -> K,
+{
+    let mut seen = HashMap::new();
+    let mut state = initial;
+
+    for i in 0.. {
+        if let Some(start) = seen.insert(key(&state), i) {
+            return Cycle {
+                start,
+                length: i - start,
+            };
+        }
+
+        state = step(&state);
+    }
+
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 6 -> 3 -> ...
+    fn step(val: &usize) -> usize {
+        if *val == 6 {
+            3
+        } else {
+            val + 1
+        }
+    }
+
+    const CYCLE: Cycle = Cycle {
+        start: 3,
+        length: 4,
+    };
+
+    #[test]
+    fn detectors_agree() {
+        assert_eq!(floyd(&0, step), CYCLE);
+        assert_eq!(brent(&0, step), CYCLE);
+        assert_eq!(hashed(0, step, |val| *val), CYCLE);
+    }
+
+    #[test]
+    fn detects_cycle_starting_at_initial_state() {
+        let step = |val: &usize| (val + 1) % 5;
+        let cycle = Cycle {
+            start: 0,
+            length: 5,
+        };
+
+        assert_eq!(floyd(&0, step), cycle);
+        assert_eq!(brent(&0, step), cycle);
+        assert_eq!(hashed(0, step, |val| *val), cycle);
+    }
+
+    #[test]
+    fn reduce() {
+        assert_eq!(CYCLE.reduce(2), 2);
+        assert_eq!(CYCLE.reduce(3), 3);
+        assert_eq!(CYCLE.reduce(7), 3);
+        assert_eq!(
+            CYCLE.reduce(1_000_000_000_001),
+            3 + (1_000_000_000_001 - 3) % 4
+        );
+    }
+
+    #[test]
+    fn extrapolate_matches_simulation() {
+        let step = |val: &usize| (step(val), *val);
+
+        for steps in 0..50 {
+            let mut state = 0;
+            let mut expected = 0;
+
+            for _ in 0..steps {
+                let (next, acc) = step(&state);
+                expected += acc;
+                state = next;
+            }
+
+            assert_eq!(CYCLE.extrapolate(0, steps, step), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn extrapolate_vec() {
+        let step = |val: &usize| {
+            let mut counts = vec![0; 7];
+            counts[*val] += 1;
+            (step(val), counts)
+        };
+
+        let counts: Vec<usize> = CYCLE.extrapolate(0, 3 + 4 * 1_000_000 + 1, step).unwrap();
+
+        assert_eq!(
+            counts,
+            [1, 1, 1, 1_000_001, 1_000_000, 1_000_000, 1_000_000]
+        );
+    }
+
+    #[test]
+    fn extrapolate_overflow() {
+        let step = |val: &usize| (step(val), 1u8);
+
+        assert_eq!(CYCLE.extrapolate(0, 255, step), Ok(255));
+        assert_eq!(CYCLE.extrapolate(0, 256, step), Err(Overflow));
+        assert_eq!(CYCLE.extrapolate(0, 3 + 4 * 300, step), Err(Overflow));
+
+        // part of a lap that fits, even though the whole lap doesn't
+        let cycle = Cycle {
+            start: 0,
+            length: 2,
+        };
+        let step = |val: &usize| (1 - val, [200u8, 100][*val]);
+
+        assert_eq!(cycle.extrapolate(0, 1, step), Ok(200));
+        assert_eq!(cycle.extrapolate(0, 2, step), Err(Overflow));
+
+        // a number of laps that doesn't even fit in the accumulator's type
+        let mut val = 0u32;
+        assert_eq!(val.scale(usize::MAX), Err(Overflow));
+    }
+}
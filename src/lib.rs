@@ -1,3 +1,11 @@
+pub mod bitset;
+pub mod cycle;
+pub mod memo;
+pub mod parse;
+pub mod rng;
+pub mod stacks;
+pub mod top_k;
+
 pub fn add(left: usize, right: usize) -> usize {
     left + right
 }
@@ -0,0 +1,191 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// A cache of computed values, optionally bounded in size.
+///
+/// A bounded cache forgets the oldest entries first once it's full.
+#[derive(Debug)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    capacity: Option<usize>,
+    order: VecDeque<K>,
+}
+
+impl<K: Hash + Eq + Clone, V> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+            capacity: None,
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn bounded(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::new()
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    pub fn insert(&mut self, key: K, val: V) {
+        let Some(capacity) = self.capacity else {
+            self.cache.insert(key, val);
+            return;
+        };
+
+        if capacity == 0 {
+            return;
+        }
+
+        if self.cache.insert(key.clone(), val).is_none() {
+            self.order.push_back(key);
+        }
+
+        while self.cache.len() > capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.cache.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.order.clear();
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A recursive function that only computes each value once.
+///
+/// `f` is handed a function to recurse with as its first argument, which goes through the cache:
+///
+/// ```
+/// use advent_2022::memo::Memoized;
+///
+/// let mut fib = Memoized::new(|fib: &mut dyn FnMut(u64) -> u64, n: u64| match n {
+///     0 | 1 => n,
+///     n => fib(n - 1) + fib(n - 2),
+/// });
+///
+/// assert_eq!(fib.call(90), 2880067194370816120);
+/// ```
+pub struct Memoized<K, V, F> {
+    memo: Memo<K, V>,
+    f: F,
+}
+
+impl<K, V, F> Memoized<K, V, F>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    pub fn new(f: F) -> Self {
+        Self {
+            memo: Memo::new(),
+            f,
+        }
+    }
+
+    pub fn bounded(capacity: usize, f: F) -> Self {
+        Self {
+            memo: Memo::bounded(capacity),
+            f,
+        }
+    }
+
+    pub fn call(&mut self, key: K) -> V {
+        call_with(&mut self.memo, &self.f, key)
+    }
+
+    pub fn memo(&self) -> &Memo<K, V> {
+        &self.memo
+    }
+}
+
+fn call_with<K, V, F>(memo: &mut Memo<K, V>, f: &F, key: K) -> V
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, K) -> V,
+{
+    if let Some(val) = memo.get(&key) {
+        return val.clone();
+    }
+
+    let val = f(&mut |key| call_with(memo, f, key), key.clone());
+    memo.insert(key, val.clone());
+
+    val
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn only_computes_once() {
+        let calls = Cell::new(0);
+
+        let mut fib = Memoized::new(|fib: &mut dyn FnMut(u64) -> u64, n: u64| {
+            calls.set(calls.get() + 1);
+
+            match n {
+                0 | 1 => n,
+                n => fib(n - 1) + fib(n - 2),
+            }
+        });
+
+        assert_eq!(fib.call(50), 12586269025);
+        assert_eq!(calls.get(), 51);
+
+        fib.call(50);
+        assert_eq!(calls.get(), 51);
+    }
+
+    #[test]
+    fn bounded() {
+        let mut memo = Memo::bounded(2);
+
+        memo.insert(1, "one");
+        memo.insert(2, "two");
+        memo.insert(1, "uno");
+        memo.insert(3, "three");
+
+        assert_eq!(memo.len(), 2);
+        assert_eq!(memo.get(&1), None);
+        assert_eq!(memo.get(&2), Some(&"two"));
+        assert_eq!(memo.get(&3), Some(&"three"));
+    }
+
+    #[test]
+    fn bounded_recursion_still_gives_the_right_answer() {
+        let mut fib = Memoized::bounded(4, |fib: &mut dyn FnMut(u64) -> u64, n: u64| match n {
+            0 | 1 => n,
+            n => fib(n - 1) + fib(n - 2),
+        });
+
+        assert_eq!(fib.call(60), 1548008755920);
+        assert!(fib.memo().len() <= 4);
+    }
+}
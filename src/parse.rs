@@ -0,0 +1,700 @@
+//! Small parser combinators for puzzle inputs.
+//!
+//! A parser is anything that implements [`Parser`], which includes every
+//! `Fn(Input) -> PResult<T>`. Parsers produce owned values, so they can be stored and reused
+//! without worrying about the lifetime of the input they run on.
+
+use std::{fmt, str::FromStr};
+
+/// A 1-indexed position in the input, the way an editor would show it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub position: Position,
+    pub expected: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: expected {}", self.position, self.expected)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// What is left to parse, along with where it is in the original input.
+#[derive(Clone, Copy, Debug)]
+pub struct Input<'a> {
+    source: &'a str,
+    offset: usize,
+    position: Position,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            offset: 0,
+            position: Position { line: 1, column: 1 },
+        }
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Skips `len` bytes, which must land on a char boundary.
+    pub fn advance(self, len: usize) -> Self {
+        let mut position = self.position;
+
+        for c in self.rest()[..len].chars() {
+            if c == '\n' {
+                position.line += 1;
+                position.column = 1;
+            } else {
+                position.column += 1;
+            }
+        }
+
+        Self {
+            offset: self.offset + len,
+            position,
+            ..self
+        }
+    }
+
+    pub fn error<T>(&self, expected: impl Into<String>) -> PResult<'a, T> {
+        Err(Error {
+            position: self.position(),
+            expected: expected.into(),
+        })
+    }
+}
+
+pub type PResult<'a, T> = Result<(Input<'a>, T), Error>;
+
+pub trait Parser<T> {
+    fn parse<'a>(&self, input: Input<'a>) -> PResult<'a, T>;
+}
+
+impl<T, F> Parser<T> for F
+where
+    F: Fn(Input<'_>) -> PResult<'_, T>,
+{
+    fn parse<'a>(&self, input: Input<'a>) -> PResult<'a, T> {
+        self(input)
+    }
+}
+
+/// Pins down the signature of a closure, so it accepts input of any lifetime.
+fn from_fn<T, F>(f: F) -> F
+where
+    F: Fn(Input<'_>) -> PResult<'_, T>,
+{
+    f
+}
+
+/// Runs `parser` over the whole of `input`.
+///
+/// Leading blank lines and trailing whitespace are ignored, since that's how puzzle inputs (and
+/// the indented raw strings in tests) tend to look. Anything else left over is an error.
+pub fn parse<T>(input: &str, parser: impl Parser<T>) -> Result<T, Error> {
+    let (input, _) = many(blank_line).parse(Input::new(input))?;
+    let (input, value) = parser.parse(input)?;
+
+    if input.rest().trim().is_empty() {
+        Ok(value)
+    } else {
+        input.error("end of input").map(|(_, value)| value)
+    }
+}
+
+pub fn tag(tag: &'static str) -> impl Parser<&'static str> {
+    from_fn(
+        move |input: Input<'_>| match input.rest().strip_prefix(tag) {
+            Some(_) => Ok((input.advance(tag.len()), tag)),
+            None => input.error(format!("{tag:?}")),
+        },
+    )
+}
+
+/// A single char that passes `predicate`, described as `expected` when it doesn't.
+pub fn char_if(expected: &'static str, predicate: impl Fn(char) -> bool) -> impl Parser<char> {
+    from_fn(move |input: Input<'_>| match input.rest().chars().next() {
+        Some(c) if predicate(c) => Ok((input.advance(c.len_utf8()), c)),
+        _ => input.error(expected),
+    })
+}
+
+/// One or more chars that pass `predicate`.
+pub fn take_while1(
+    expected: &'static str,
+    predicate: impl Fn(char) -> bool,
+) -> impl Parser<String> {
+    from_fn(move |input: Input<'_>| {
+        let rest = input.rest();
+        let len = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+
+        if len == 0 {
+            input.error(expected)
+        } else {
+            Ok((input.advance(len), rest[..len].to_string()))
+        }
+    })
+}
+
+/// Zero or more spaces or tabs, but never a line break.
+pub fn spaces(input: Input<'_>) -> PResult<'_, ()> {
+    let rest = input.rest();
+    let len = rest.find(|c| c != ' ' && c != '\t').unwrap_or(rest.len());
+
+    Ok((input.advance(len), ()))
+}
+
+pub fn unsigned<T: FromStr>() -> impl Parser<T> {
+    from_fn(move |input: Input<'_>| {
+        let (rest, digits) =
+            take_while1("an unsigned integer", |c| c.is_ascii_digit()).parse(input)?;
+
+        match digits.parse() {
+            Ok(val) => Ok((rest, val)),
+            Err(_) => input.error("an integer small enough to fit"),
+        }
+    })
+}
+
+pub fn signed<T: FromStr>() -> impl Parser<T> {
+    from_fn(move |input: Input<'_>| {
+        let (rest, sign) = opt(char_if("a sign", |c| c == '-' || c == '+')).parse(input)?;
+        let (rest, digits) = take_while1("an integer", |c| c.is_ascii_digit()).parse(rest)?;
+
+        let number = match sign {
+            Some('-') => format!("-{digits}"),
+            _ => digits,
+        };
+
+        match number.parse() {
+            Ok(val) => Ok((rest, val)),
+            Err(_) => input.error("an integer small enough to fit"),
+        }
+    })
+}
+
+pub fn map<T, U>(parser: impl Parser<T>, f: impl Fn(T) -> U) -> impl Parser<U> {
+    from_fn(move |input: Input<'_>| {
+        let (input, val) = parser.parse(input)?;
+        Ok((input, f(val)))
+    })
+}
+
+/// Like [`map`], but `f` can reject the value, which is reported at the start of it.
+pub fn try_map<T, U>(
+    parser: impl Parser<T>,
+    expected: &'static str,
+    f: impl Fn(T) -> Option<U>,
+) -> impl Parser<U> {
+    from_fn(move |input: Input<'_>| {
+        let (rest, val) = parser.parse(input)?;
+
+        match f(val) {
+            Some(val) => Ok((rest, val)),
+            None => input.error(expected),
+        }
+    })
+}
+
+pub fn pair<T, U>(first: impl Parser<T>, second: impl Parser<U>) -> impl Parser<(T, U)> {
+    from_fn(move |input: Input<'_>| {
+        let (input, first) = first.parse(input)?;
+        let (input, second) = second.parse(input)?;
+
+        Ok((input, (first, second)))
+    })
+}
+
+pub fn preceded<T, U>(first: impl Parser<T>, second: impl Parser<U>) -> impl Parser<U> {
+    map(pair(first, second), |(_, second)| second)
+}
+
+pub fn terminated<T, U>(first: impl Parser<T>, second: impl Parser<U>) -> impl Parser<T> {
+    map(pair(first, second), |(first, _)| first)
+}
+
+pub fn opt<T>(parser: impl Parser<T>) -> impl Parser<Option<T>> {
+    from_fn(move |input: Input<'_>| match parser.parse(input) {
+        Ok((input, val)) => Ok((input, Some(val))),
+        Err(_) => Ok((input, None)),
+    })
+}
+
+/// Tries `first`, then `second`.
+///
+/// When both fail, the error that got further into the input wins, since that's usually the one
+/// the input was meant to match.
+pub fn alt<T>(first: impl Parser<T>, second: impl Parser<T>) -> impl Parser<T> {
+    from_fn(move |input: Input<'_>| {
+        let first = match first.parse(input) {
+            Ok(ok) => return Ok(ok),
+            Err(err) => err,
+        };
+
+        let second = match second.parse(input) {
+            Ok(ok) => return Ok(ok),
+            Err(err) => err,
+        };
+
+        Err(match first.position.cmp(&second.position) {
+            std::cmp::Ordering::Greater => first,
+            std::cmp::Ordering::Less => second,
+            std::cmp::Ordering::Equal => Error {
+                expected: format!("{} or {}", first.expected, second.expected),
+                ..first
+            },
+        })
+    })
+}
+
+/// Whether a parser that started at `input` got past anything other than whitespace before
+/// failing with `err`, i.e. the input was clearly meant to match it.
+fn is_committed(input: Input<'_>, err: &Error) -> bool {
+    let mut position = input.position();
+
+    for c in input.rest().chars() {
+        if position >= err.position {
+            return false;
+        }
+
+        if !c.is_whitespace() {
+            return true;
+        }
+
+        if c == '\n' {
+            position.line += 1;
+            position.column = 1;
+        } else {
+            position.column += 1;
+        }
+    }
+
+    false
+}
+
+/// Zero or more of `parser`, stopping at a match that consumes nothing or at a failure.
+///
+/// A failure that happens partway into a match is an error rather than the end of the list, so
+/// that the error points at the broken item instead of at whatever comes after the list.
+/// Whitespace doesn't count, so trailing blank lines don't break anything.
+pub fn many<T>(parser: impl Parser<T>) -> impl Parser<Vec<T>> {
+    from_fn(move |mut input: Input<'_>| {
+        let mut values = vec![];
+
+        loop {
+            match parser.parse(input) {
+                Ok((rest, _)) if rest.offset == input.offset => break,
+                Ok((rest, val)) => {
+                    values.push(val);
+                    input = rest;
+                }
+                Err(err) if is_committed(input, &err) => return Err(err),
+                Err(_) => break,
+            }
+        }
+
+        Ok((input, values))
+    })
+}
+
+/// One or more of `parser`, with `separator` in between each.
+///
+/// Like [`many`], an item that fails partway through is an error.
+pub fn separated<T, S>(parser: impl Parser<T>, separator: impl Parser<S>) -> impl Parser<Vec<T>> {
+    from_fn(move |input: Input<'_>| {
+        let (mut input, first) = parser.parse(input)?;
+        let mut values = vec![first];
+
+        while let Ok((after_separator, _)) = separator.parse(input) {
+            match parser.parse(after_separator) {
+                Ok((rest, val)) => {
+                    values.push(val);
+                    input = rest;
+                }
+                Err(err) if is_committed(after_separator, &err) => return Err(err),
+                Err(_) => break,
+            }
+        }
+
+        Ok((input, values))
+    })
+}
+
+/// The end of a line, allowing for trailing spaces and CRLF.
+pub fn line_end(input: Input<'_>) -> PResult<'_, ()> {
+    let (input, _) = spaces(input)?;
+    let rest = input.rest();
+
+    if rest.is_empty() {
+        Ok((input, ()))
+    } else if rest.starts_with('\n') {
+        Ok((input.advance(1), ()))
+    } else if rest.starts_with("\r\n") {
+        Ok((input.advance(2), ()))
+    } else {
+        input.error("end of line")
+    }
+}
+
+/// `parser` followed by the end of the line.
+pub fn line<T>(parser: impl Parser<T>) -> impl Parser<T> {
+    terminated(parser, line_end)
+}
+
+/// A line starting with `label`, followed by `parser`.
+///
+/// Indentation and spaces around the label are skipped, so `field("Test:", ..)` matches
+/// `"  Test: divisible by 23"`.
+pub fn field<T>(label: &'static str, parser: impl Parser<T>) -> impl Parser<T> {
+    line(preceded(pair(pair(spaces, tag(label)), spaces), parser))
+}
+
+/// One or more `parser`s separated by blank lines.
+///
+/// `parser` is expected to consume the line break at the end of its last line, e.g. by using
+/// [`line`].
+pub fn blocks<T>(parser: impl Parser<T>) -> impl Parser<Vec<T>> {
+    separated(parser, blank_lines)
+}
+
+/// `parser` on every line through to the end of the input, skipping blank lines.
+///
+/// Unlike `many(line(parser))`, a line that doesn't match at all is an error too, rather than
+/// where the list ends.
+pub fn lines<T>(parser: impl Parser<T>) -> impl Parser<Vec<T>> {
+    let parser = line(parser);
+
+    from_fn(move |mut input: Input<'_>| {
+        let mut values = vec![];
+
+        loop {
+            (input, _) = many(blank_line).parse(input)?;
+
+            if input.is_empty() {
+                return Ok((input, values));
+            }
+
+            let (rest, val) = parser.parse(input)?;
+            values.push(val);
+            input = rest;
+        }
+    })
+}
+
+/// One or more blank lines.
+pub fn blank_lines(input: Input<'_>) -> PResult<'_, ()> {
+    let (rest, lines) = many(blank_line).parse(input)?;
+
+    if lines.is_empty() {
+        input.error("a blank line")
+    } else {
+        Ok((rest, ()))
+    }
+}
+
+/// A line with nothing but whitespace on it.
+///
+/// Unlike `line(spaces)`, this never fails partway through a line with text on it.
+pub fn blank_line(input: Input<'_>) -> PResult<'_, ()> {
+    let rest = input.rest();
+    let line = rest.split('\n').next().unwrap_or_default();
+
+    if rest.is_empty() || !line.trim().is_empty() {
+        input.error("a blank line")
+    } else {
+        Ok((input.advance((line.len() + 1).min(rest.len())), ()))
+    }
+}
+
+/// Lines of cells up to the next blank line, where `cell` maps each char to a value.
+///
+/// Rows don't have to be the same length.
+pub fn grid<T>(
+    expected: &'static str,
+    cell: impl Fn(char) -> Option<T>,
+) -> impl Parser<Vec<Vec<T>>> {
+    let row = from_fn(move |mut input: Input<'_>| {
+        let mut row = vec![];
+
+        while let Some(c) = input
+            .rest()
+            .chars()
+            .next()
+            .filter(|c| *c != '\n' && *c != '\r')
+        {
+            match cell(c) {
+                Some(val) => row.push(val),
+                None => return input.error(expected),
+            }
+
+            input = input.advance(c.len_utf8());
+        }
+
+        if row.is_empty() {
+            input.error(expected)
+        } else {
+            Ok((input, row))
+        }
+    });
+
+    many(line(row))
+}
+
+/// Splits `input` into groups of lines separated by blank lines.
+///
+/// A blank line is one with nothing but whitespace on it, so a line holding `0` is never a
+/// separator. Runs of blank lines count as a single separator, and the lines in each group have
+/// their indentation and any trailing `\r` trimmed off.
+pub fn groups(input: &str) -> Groups<'_> {
+    Groups {
+        rest: input,
+        line: 1,
+    }
+}
+
+pub struct Groups<'a> {
+    rest: &'a str,
+    line: usize,
+}
+
+impl<'a> Groups<'a> {
+    fn split_line(&self) -> (&'a str, &'a str) {
+        match self.rest.split_once('\n') {
+            Some((line, rest)) => (line, rest),
+            None => (self.rest, ""),
+        }
+    }
+}
+
+impl<'a> Iterator for Groups<'a> {
+    type Item = Group<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+
+            let (line, rest) = self.split_line();
+
+            if !line.trim().is_empty() {
+                break;
+            }
+
+            self.rest = rest;
+            self.line += 1;
+        }
+
+        let start = self.rest;
+        let first_line = self.line;
+        let mut len = 0;
+
+        while !self.rest.is_empty() {
+            let (line, rest) = self.split_line();
+
+            if line.trim().is_empty() {
+                break;
+            }
+
+            len += self.rest.len() - rest.len();
+            self.rest = rest;
+            self.line += 1;
+        }
+
+        Some(Group {
+            lines: start[..len].lines(),
+            line: first_line,
+        })
+    }
+}
+
+/// The trimmed lines of a single group from [`groups`].
+pub struct Group<'a> {
+    lines: std::str::Lines<'a>,
+    line: usize,
+}
+
+impl<'a> Group<'a> {
+    /// Pairs each line with the position of its first non-whitespace char, for error messages.
+    pub fn numbered(self) -> impl Iterator<Item = (Position, &'a str)> {
+        let first_line = self.line;
+
+        self.lines.enumerate().map(move |(i, line)| {
+            let position = Position {
+                line: first_line + i,
+                column: line.chars().take_while(|c| c.is_whitespace()).count() + 1,
+            };
+
+            (position, line.trim())
+        })
+    }
+}
+
+impl<'a> Iterator for Group<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.line += 1;
+        self.lines.next().map(str::trim)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers() {
+        assert_eq!(parse("42", unsigned::<usize>()), Ok(42));
+        assert_eq!(parse("-42", signed::<isize>()), Ok(-42));
+        assert_eq!(parse("+42", signed::<isize>()), Ok(42));
+
+        let err = parse("300", unsigned::<u8>()).unwrap_err();
+        assert_eq!(err.position, Position { line: 1, column: 1 });
+    }
+
+    #[test]
+    fn lists() {
+        let list = || separated(unsigned::<usize>(), tag(", "));
+
+        assert_eq!(parse("1, 2, 3", list()), Ok(vec![1, 2, 3]));
+
+        let err = parse("1, 2, x", list()).unwrap_err();
+        assert_eq!(err.position, Position { line: 1, column: 5 });
+        assert_eq!(err.expected, "end of input");
+    }
+
+    #[test]
+    fn every_line() {
+        let numbers = || lines(preceded(spaces, unsigned::<usize>()));
+
+        assert_eq!(parse("\n  1\n\n  2  \r\n  ", numbers()), Ok(vec![1, 2]));
+
+        let err = parse("1\n2\nx\n3", numbers()).unwrap_err();
+        assert_eq!(err.position, Position { line: 3, column: 1 });
+        assert_eq!(err.expected, "an unsigned integer");
+    }
+
+    #[test]
+    fn blocks_of_fields() {
+        let input = r#"
+            a: 1
+            b: -2
+
+            a: 3
+            b: 4
+        "#;
+
+        let block = pair(
+            field("a:", unsigned::<usize>()),
+            field("b:", signed::<isize>()),
+        );
+
+        assert_eq!(parse(input, blocks(block)), Ok(vec![(1, -2), (3, 4)]));
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let input = "a: 1\nb: 2\n\na: 3\nc: 4\n";
+
+        let block = pair(
+            field("a:", unsigned::<usize>()),
+            field("b:", unsigned::<usize>()),
+        );
+
+        let err = parse(input, blocks(block)).unwrap_err();
+
+        assert_eq!(err.position, Position { line: 5, column: 1 });
+        assert_eq!(err.to_string(), r#"line 5, column 1: expected "b:""#);
+    }
+
+    #[test]
+    fn trailing_whitespace_is_not_a_broken_item() {
+        let numbers = many(line(preceded(spaces, unsigned::<usize>())));
+
+        assert_eq!(parse("  1\n  2\n    ", numbers), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn grids() {
+        let digit = |c: char| c.to_digit(10);
+
+        assert_eq!(
+            parse("12\r\n345\n", grid("a digit", digit)),
+            Ok(vec![vec![1, 2], vec![3, 4, 5]])
+        );
+
+        let err = grid("a digit", digit)
+            .parse(Input::new("12\n3x5\n"))
+            .unwrap_err();
+
+        assert_eq!(err.position, Position { line: 2, column: 2 });
+        assert_eq!(err.expected, "a digit");
+    }
+
+    #[test]
+    fn alt_reports_the_furthest_error() {
+        let parser = alt(
+            map(pair(tag("move "), unsigned::<usize>()), |(_, n)| n),
+            map(tag("stay"), |_| 0),
+        );
+
+        let err = parser.parse(Input::new("move x")).unwrap_err();
+        assert_eq!(err.position.column, 6);
+        assert_eq!(err.expected, "an unsigned integer");
+
+        let err = parser.parse(Input::new("jump")).unwrap_err();
+        assert_eq!(err.expected, r#""move " or "stay""#);
+    }
+
+    #[test]
+    fn groups_split_on_blank_lines_only() {
+        let input = "\n  1\r\n  0\r\n \r\n\r\n  2\n\n\tx\n";
+
+        let groups: Vec<Vec<&str>> = super::groups(input).map(Iterator::collect).collect();
+
+        assert_eq!(groups, [vec!["1", "0"], vec!["2"], vec!["x"]]);
+    }
+
+    #[test]
+    fn groups_know_their_line_numbers() {
+        let input = "1\n\n  2\n  3\n";
+
+        let numbered: Vec<_> = super::groups(input).flat_map(Group::numbered).collect();
+
+        assert_eq!(
+            numbered,
+            [
+                (Position { line: 1, column: 1 }, "1"),
+                (Position { line: 3, column: 3 }, "2"),
+                (Position { line: 4, column: 3 }, "3"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! Moving items off the top of one stack onto another, like day 5's cranes do.
+//!
+//! Both moves take the items off in one `drain` rather than popping them one at a time, so
+//! neither allocates. `from` has to have at least `count` items on it.
+
+/// Moves the top `count` items one at a time, so they end up in reverse order.
+pub fn move_reversed<T>(from: &mut Vec<T>, to: &mut Vec<T>, count: usize) {
+    let height = from.len();
+    to.extend(from.drain(height - count..).rev());
+}
+
+/// Moves the top `count` items all at once, so they keep their order.
+pub fn move_in_order<T>(from: &mut Vec<T>, to: &mut Vec<T>, count: usize) {
+    let height = from.len();
+    to.extend(from.drain(height - count..));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves() {
+        let (mut from, mut to) = (vec![1, 2, 3], vec![9]);
+        move_reversed(&mut from, &mut to, 2);
+        assert_eq!((from, to), (vec![1], vec![9, 3, 2]));
+
+        let (mut from, mut to) = (vec![1, 2, 3], vec![9]);
+        move_in_order(&mut from, &mut to, 2);
+        assert_eq!((from, to), (vec![1], vec![9, 2, 3]));
+    }
+}
@@ -0,0 +1,126 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// Keeps the `k` largest values pushed into it, in O(log k) per push and O(k) space.
+#[derive(Clone, Debug)]
+pub struct TopK<T> {
+    // a min-heap, so the smallest of the values being kept is the one that gets bumped
+    heap: BinaryHeap<Reverse<T>>,
+    k: usize,
+}
+
+impl<T: Ord> TopK<T> {
+    pub fn new(k: usize) -> Self {
+        Self {
+            // `k` can come straight from the user, so don't trust it with an allocation
+            heap: BinaryHeap::with_capacity(k.min(1024)),
+            k,
+        }
+    }
+
+    pub fn push(&mut self, val: T) {
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(val));
+        } else if let Some(mut smallest) = self.heap.peek_mut() {
+            if val > smallest.0 {
+                smallest.0 = val;
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// The smallest value that is still in the top `k`.
+    pub fn min(&self) -> Option<&T> {
+        self.heap.peek().map(|val| &val.0)
+    }
+
+    /// The values kept so far, largest first.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        // sorting `Reverse`s ascending puts the largest value first
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|val| val.0)
+            .collect()
+    }
+}
+
+impl<T: Ord> Extend<T> for TopK<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|val| self.push(val));
+    }
+}
+
+/// The `k` largest values, largest first.
+pub fn top_k<T: Ord>(iter: impl IntoIterator<Item = T>, k: usize) -> Vec<T> {
+    let mut top = TopK::new(k);
+    top.extend(iter);
+    top.into_sorted_vec()
+}
+
+/// The `k` largest values along with their index in `iter`, largest first.
+///
+/// Of values that are equal, the one that came first wins.
+pub fn top_k_indexed<T: Ord>(iter: impl IntoIterator<Item = T>, k: usize) -> Vec<(usize, T)> {
+    let indexed = iter
+        .into_iter()
+        .enumerate()
+        .map(|(i, val)| (val, Reverse(i)));
+
+    top_k(indexed, k)
+        .into_iter()
+        .map(|(val, Reverse(i))| (i, val))
+        .collect()
+}
+
+/// The `K` largest values, largest first, or `None` if there are fewer than `K` of them.
+pub fn top<const K: usize, T: Ord>(iter: impl IntoIterator<Item = T>) -> Option<[T; K]> {
+    top_k(iter, K).try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn largest_first() {
+        assert_eq!(top_k([5, 1, 9, 3, 7], 3), [9, 7, 5]);
+        assert_eq!(top_k([5, 1], 3), [5, 1]);
+        assert_eq!(top_k([5, 1], 0), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn indexed() {
+        assert_eq!(top_k_indexed([5, 9, 1, 9, 7], 3), [(1, 9), (3, 9), (4, 7)]);
+    }
+
+    #[test]
+    fn fixed_size() {
+        assert_eq!(top::<2, _>([5, 1, 9, 3]), Some([9, 5]));
+        assert_eq!(top::<2, _>([5]), None);
+    }
+
+    #[test]
+    fn streaming() {
+        let mut top = TopK::new(2);
+
+        top.push(3);
+        assert_eq!(top.min(), Some(&3));
+
+        top.extend([1, 4, 2]);
+        assert_eq!(top.min(), Some(&3));
+        assert_eq!(top.len(), 2);
+        assert_eq!(top.into_sorted_vec(), [4, 3]);
+    }
+
+    #[test]
+    fn huge_k() {
+        assert_eq!(top_k([5, 1, 9], usize::MAX), [9, 5, 1]);
+    }
+}